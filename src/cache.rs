@@ -0,0 +1,578 @@
+use crate::error::SharedError;
+use crate::import::LazyImage;
+use crate::ktx2::Ktx2Image;
+use crate::resolver::{LoadOptions, LoadedResources, Resource, ResourceData, ResourceResolver};
+use crate::sandbox;
+use image::DynamicImage;
+use std::cell::RefCell;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Cache for buffers and images, shared by multiple imports
+///
+/// Set via [`ImportOptions::with_cache`](../import/struct.ImportOptions.html#method.with_cache), e.g. when importing
+/// many models of a modular kit referencing the same textures. External resources (relative paths, `file:` and
+/// `http[s]://` URIs) are looked up by their normalized path or URL before they are requested from the resolver, so
+/// each of them is only loaded (and decoded) once, even if models in different directories reference it via different
+/// relative paths. The data is shared via reference counting between the cache and all imported models.
+///
+/// Resources requested by imports running at the same time are loaded once as well: later imports wait for the
+/// resources still being loaded by earlier ones instead of requesting them again. Decoding happens per import until
+/// the image is cached.
+///
+/// Embedded data (`data:` URIs, buffer views and the binary GLB chunk) is not cached. Cached images are still checked
+/// against the [`ImportLimits`](../limits/struct.ImportLimits.html) of each import.
+///
+/// Cloning the cache is cheap, all clones share the same entries.
+///
+/// ```rust
+/// use three_d_gltf_import::cache::ImportCache;
+/// use three_d_gltf_import::import::ImportOptions;
+///
+/// let cache = ImportCache::new();
+/// let options = ImportOptions::new().with_cache(cache.clone());
+/// // import the models using `options`, then free the memory once they are no longer needed
+/// cache.clear();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ImportCache {
+    entries: Rc<RefCell<CacheEntries>>,
+}
+
+#[derive(Default)]
+struct CacheEntries {
+    buffers: HashMap<CacheKey, Rc<ResourceData>>,
+    /// Images with the size of their encoded data
+    images: HashMap<CacheKey, (SharedImage, usize)>,
+    /// Resources being loaded, with the imports waiting for them
+    in_flight: HashMap<CacheKey, Vec<Waiter>>,
+}
+
+impl fmt::Debug for CacheEntries {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CacheEntries")
+            .field("buffers", &self.buffers)
+            .field("images", &self.images)
+            .field("in_flight", &self.in_flight.keys())
+            .finish()
+    }
+}
+
+/// Callback of an import waiting for a resource loaded by another one
+type Waiter = Box<dyn FnOnce(io::Result<ResourceData>)>;
+
+/// Identifies a resource independent of the document referencing it
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum CacheKey {
+    /// Normalized path of a relative or `file:` resource
+    Path(PathBuf),
+    Url(String),
+}
+
+impl From<&Resource> for CacheKey {
+    fn from(resource: &Resource) -> Self {
+        match resource {
+            Resource::External(url) => CacheKey::Url(url.clone()),
+            _ => CacheKey::Path(sandbox::normalize(&resource.path())),
+        }
+    }
+}
+
+impl ImportCache {
+    /// An empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of cached buffers and images
+    pub fn len(&self) -> usize {
+        let entries = self.entries.borrow();
+        entries.buffers.len() + entries.images.len()
+    }
+
+    /// Whether nothing has been cached yet
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all cached buffers and images
+    ///
+    /// Models that have already been imported keep their data, resources being loaded are not affected.
+    pub fn clear(&self) {
+        let mut entries = self.entries.borrow_mut();
+        entries.buffers.clear();
+        entries.images.clear();
+    }
+
+    pub(crate) fn buffer(&self, resource: &Resource) -> Option<Rc<ResourceData>> {
+        self.entries
+            .borrow()
+            .buffers
+            .get(&CacheKey::from(resource))
+            .cloned()
+    }
+
+    pub(crate) fn insert_buffer(&self, resource: &Resource, buffer: Rc<ResourceData>) {
+        self.entries
+            .borrow_mut()
+            .buffers
+            .insert(resource.into(), buffer);
+    }
+
+    /// The cached image, if it fits the import
+    ///
+    /// Decoded and KTX2 images fit any import, lazy images only imports keeping images encoded.
+    pub(crate) fn image(&self, resource: &Resource, lazy: bool) -> Option<(SharedImage, usize)> {
+        match self.entries.borrow().images.get(&CacheKey::from(resource)) {
+            Some((SharedImage::Lazy(_), _)) if !lazy => None,
+            cached => cached.cloned(),
+        }
+    }
+
+    pub(crate) fn insert_image(&self, resource: &Resource, image: SharedImage, bytes: usize) {
+        self.entries
+            .borrow_mut()
+            .images
+            .insert(resource.into(), (image, bytes));
+    }
+
+    /// Loads `resources` via `resolver`, waiting for the ones that other imports are loading already
+    ///
+    /// Resources loaded for waiting imports are shared via
+    /// [`ResourceData::Slice`](../resolver/enum.ResourceData.html#variant.Slice) and their errors via
+    /// [`SharedError`](../error/struct.SharedError.html). The waiting imports are only notified after the loading
+    /// import, which usually caches the resources meanwhile.
+    pub(crate) fn load(
+        &self,
+        resolver: &dyn ResourceResolver,
+        resources: Vec<Resource>,
+        options: LoadOptions,
+        on_done: Box<dyn FnOnce(LoadedResources)>,
+    ) {
+        let total = resources.len();
+        let mut requested = Vec::with_capacity(total);
+        let mut waiting = Vec::new();
+        {
+            let mut entries = self.entries.borrow_mut();
+            for resource in resources {
+                match entries.in_flight.entry(CacheKey::from(&resource)) {
+                    Entry::Occupied(_) => waiting.push(resource),
+                    Entry::Vacant(entry) => {
+                        entry.insert(Vec::new());
+                        requested.push(resource);
+                    }
+                }
+            }
+        }
+        if waiting.is_empty() {
+            let in_flight = InFlight {
+                entries: self.entries.clone(),
+                keys: requested.iter().map(CacheKey::from).collect(),
+            };
+            return resolver.load_with_options(
+                requested,
+                options,
+                Box::new(move |loaded| in_flight.finish(loaded, on_done)),
+            );
+        }
+
+        let pending = Rc::new(RefCell::new(PendingLoad {
+            loaded: LoadedResources::with_capacity(total),
+            total,
+            requested: requested.len(),
+            batch_progress: if requested.is_empty() { 1.0 } else { 0.0 },
+            waited: 0,
+            remaining: waiting.len() + usize::from(!requested.is_empty()),
+            options,
+            on_done: Some(on_done),
+        }));
+        for resource in waiting {
+            let pending = pending.clone();
+            let key = CacheKey::from(&resource);
+            let waiter: Waiter = Box::new(move |data| {
+                {
+                    let mut pending = pending.borrow_mut();
+                    pending.loaded.insert(resource, data);
+                    pending.waited += 1;
+                    pending.remaining -= 1;
+                    pending.report_progress();
+                }
+                PendingLoad::finish(&pending);
+            });
+            if let Some(waiters) = self.entries.borrow_mut().in_flight.get_mut(&key) {
+                waiters.push(waiter);
+            }
+        }
+        if requested.is_empty() {
+            return;
+        }
+
+        let in_flight = InFlight {
+            entries: self.entries.clone(),
+            keys: requested.iter().map(CacheKey::from).collect(),
+        };
+        let mut options = LoadOptions::new().with_progress({
+            let pending = pending.clone();
+            move |fraction| {
+                let mut pending = pending.borrow_mut();
+                pending.batch_progress = fraction;
+                pending.report_progress();
+            }
+        });
+        if let Some(max) = pending.borrow().options.max_bytes() {
+            options = options.with_max_bytes(max);
+        }
+        resolver.load_with_options(
+            requested,
+            options,
+            Box::new(move |loaded| {
+                in_flight.finish(
+                    loaded,
+                    Box::new(move |loaded| {
+                        {
+                            let mut pending = pending.borrow_mut();
+                            pending.loaded.extend(loaded);
+                            pending.batch_progress = 1.0;
+                            pending.remaining -= 1;
+                        }
+                        PendingLoad::finish(&pending);
+                    }),
+                )
+            }),
+        );
+    }
+}
+
+/// Resources requested from the resolver by an import
+///
+/// Notifies the imports waiting for them once loaded, or with an error if the resolver drops its callback.
+struct InFlight {
+    entries: Rc<RefCell<CacheEntries>>,
+    keys: Vec<CacheKey>,
+}
+
+impl InFlight {
+    /// Passes the loaded resources to `on_done`, then to the imports waiting for them
+    fn finish(mut self, mut loaded: LoadedResources, on_done: Box<dyn FnOnce(LoadedResources)>) {
+        let mut waiters = self.take_waiters();
+        let mut shared = Vec::with_capacity(waiters.len());
+        for (resource, data) in loaded.iter_mut() {
+            let waiters = match waiters.remove(&CacheKey::from(resource)) {
+                Some(waiters) if !waiters.is_empty() => waiters,
+                _ => continue,
+            };
+            let shared_data = match std::mem::replace(data, Ok(ResourceData::Owned(Vec::new()))) {
+                Ok(data) => Ok(Rc::new(data)),
+                Err(err) => Err(Arc::new(err)),
+            };
+            *data = share(&shared_data);
+            shared.push((shared_data, waiters));
+        }
+        on_done(loaded);
+
+        for (data, waiters) in shared {
+            for waiter in waiters {
+                waiter(share(&data));
+            }
+        }
+        // Resources the resolver didn't return
+        for waiter in waiters.into_values().flatten() {
+            waiter(Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "resource has not been loaded by the resolver",
+            )));
+        }
+    }
+
+    fn take_waiters(&mut self) -> HashMap<CacheKey, Vec<Waiter>> {
+        let mut entries = self.entries.borrow_mut();
+        self.keys
+            .drain(..)
+            .filter_map(|key| {
+                let waiters = entries.in_flight.remove(&key)?;
+                Some((key, waiters))
+            })
+            .collect()
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        for waiter in self.take_waiters().into_values().flatten() {
+            waiter(Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                "loading the resource has been aborted",
+            )));
+        }
+    }
+}
+/// Loaded data or its error, shared by multiple imports
+type SharedData = Result<Rc<ResourceData>, Arc<io::Error>>;
+
+fn share(data: &SharedData) -> io::Result<ResourceData> {
+    match data {
+        Ok(data) => Ok(ResourceData::Slice(data.clone(), 0..data.len())),
+        Err(err) => Err(io::Error::new(err.kind(), SharedError(err.clone()))),
+    }
+}
+
+/// Resources of an import that waits for other imports loading some of them
+struct PendingLoad {
+    loaded: LoadedResources,
+    total: usize,
+    /// Number of resources requested from the resolver by this import
+    requested: usize,
+    /// Progress within the batch requested from the resolver
+    batch_progress: f32,
+    /// Number of resources received from other imports
+    waited: usize,
+    /// Number of resources still loaded by other imports, plus one while the batch is being loaded
+    remaining: usize,
+    options: LoadOptions,
+    on_done: Option<Box<dyn FnOnce(LoadedResources)>>,
+}
+
+impl PendingLoad {
+    fn report_progress(&self) {
+        let finished = self.waited as f32 + self.batch_progress * self.requested as f32;
+        self.options.report_progress(finished / self.total as f32);
+    }
+
+    /// Calls `on_done` once all resources have been loaded
+    fn finish(pending: &RefCell<Self>) {
+        let finished = {
+            let mut pending = pending.borrow_mut();
+            match pending.remaining {
+                0 => pending
+                    .on_done
+                    .take()
+                    .map(|on_done| (on_done, std::mem::take(&mut pending.loaded))),
+                _ => None,
+            }
+        };
+        if let Some((on_done, loaded)) = finished {
+            on_done(loaded);
+        }
+    }
+}
+
+/// An imported image, shared between the cache and the imported models
+#[derive(Clone, Debug)]
+pub(crate) enum SharedImage {
+    Decoded(Rc<DynamicImage>),
+    Lazy(Rc<LazyImage>),
+    Compressed(Rc<Ktx2Image>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{ImportError, ResourceErrorKind};
+    use crate::import::{GltfImporter, ImportOptions, ImportedGltfModel};
+    use crate::resolver::{LoadedResources, LoaderResolver, ResourceResolver};
+    use gltf::Gltf;
+    use std::path::PathBuf;
+
+    /// Resolver counting the requested resources
+    #[derive(Clone, Default)]
+    struct CountingResolver {
+        requested: Rc<RefCell<usize>>,
+    }
+
+    impl ResourceResolver for CountingResolver {
+        fn load(&self, resources: Vec<Resource>, on_done: Box<dyn FnOnce(LoadedResources)>) {
+            *self.requested.borrow_mut() += resources.len();
+            LoaderResolver.load(resources, on_done)
+        }
+    }
+
+    fn import_cube(options: ImportOptions) -> ImportedGltfModel {
+        let base = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Cube/glTF"
+        ));
+        let gltf = Gltf::open(base.join("Cube.gltf")).unwrap();
+        let result = Rc::new(RefCell::new(None));
+        GltfImporter::import_with_options(gltf, Some(base), options, {
+            let result = result.clone();
+            move |imported| *result.borrow_mut() = Some(imported.unwrap())
+        });
+
+        let imported = result.borrow_mut().take();
+        imported.unwrap()
+    }
+
+    type PendingBatch = (Vec<Resource>, Box<dyn FnOnce(LoadedResources)>);
+
+    /// Resolver loading 4 zero bytes for each resource once `run` is called, counting the requested resources
+    #[derive(Clone, Default)]
+    struct KitResolver {
+        pending: Rc<RefCell<Vec<PendingBatch>>>,
+        requested: Rc<RefCell<Vec<Resource>>>,
+    }
+
+    impl KitResolver {
+        fn run(&self) {
+            loop {
+                let next = self.pending.borrow_mut().pop();
+                let (resources, on_done) = match next {
+                    Some(next) => next,
+                    None => return,
+                };
+                let loaded = resources
+                    .into_iter()
+                    .map(|resource| (resource, Ok(vec![0; 4].into())))
+                    .collect();
+                on_done(loaded);
+            }
+        }
+    }
+
+    impl ResourceResolver for KitResolver {
+        fn load(&self, resources: Vec<Resource>, on_done: Box<dyn FnOnce(LoadedResources)>) {
+            self.requested
+                .borrow_mut()
+                .extend(resources.iter().cloned());
+            self.pending.borrow_mut().push((resources, on_done));
+        }
+    }
+
+    type ImportResult = Rc<RefCell<Option<Result<ImportedGltfModel, ImportError>>>>;
+
+    /// Imports a model of a kit, referencing a buffer via `uri` from the directory `base`
+    fn import_kit_model(base: &str, uri: &str, options: ImportOptions) -> ImportResult {
+        let json = format!(
+            r#"{{
+                "asset": {{ "version": "2.0" }},
+                "buffers": [{{ "uri": "{}", "byteLength": 4 }}]
+            }}"#,
+            uri
+        );
+        let gltf = Gltf::from_slice(json.as_bytes()).unwrap();
+        let result = Rc::new(RefCell::new(None));
+        GltfImporter::import_with_options(gltf, Some(PathBuf::from(base)), options, {
+            let result = result.clone();
+            move |imported| *result.borrow_mut() = Some(imported)
+        });
+        result
+    }
+
+    fn kit_buffer(result: &ImportResult) -> Rc<ResourceData> {
+        let result = result.borrow();
+        let model = result.as_ref().unwrap().as_ref().unwrap();
+        model.buffers()[&0].clone()
+    }
+
+    #[test]
+    fn test_cache_keys_are_normalized() {
+        let resolver = KitResolver::default();
+        let options = ImportOptions::new()
+            .with_resolver(resolver.clone())
+            .with_cache(ImportCache::new());
+
+        let first = import_kit_model("/kit/a", "../shared/data.bin", options.clone());
+        resolver.run();
+        let second = import_kit_model("/kit/b", "./../shared/data.bin", options.clone());
+        let third = import_kit_model("/kit/a", "file:///kit/shared/data.bin", options);
+        resolver.run();
+
+        assert_eq!(resolver.requested.borrow().len(), 1);
+        assert!(Rc::ptr_eq(&kit_buffer(&first), &kit_buffer(&second)));
+        assert!(Rc::ptr_eq(&kit_buffer(&first), &kit_buffer(&third)));
+    }
+
+    #[test]
+    fn test_concurrent_imports_load_resources_once() {
+        let resolver = KitResolver::default();
+        let options = ImportOptions::new()
+            .with_resolver(resolver.clone())
+            .with_cache(ImportCache::new());
+
+        let first = import_kit_model("/kit/a", "../shared/data.bin", options.clone());
+        let second = import_kit_model("/kit/b", "../shared/data.bin", options);
+        assert!(first.borrow().is_none());
+        assert!(second.borrow().is_none());
+
+        resolver.run();
+        assert_eq!(resolver.requested.borrow().len(), 1);
+        assert!(Rc::ptr_eq(&kit_buffer(&first), &kit_buffer(&second)));
+    }
+
+    #[test]
+    fn test_waiting_import_fails_if_resolver_drops_callback() {
+        let resolver = KitResolver::default();
+        let options = ImportOptions::new()
+            .with_resolver(resolver.clone())
+            .with_cache(ImportCache::new());
+
+        let first = import_kit_model("/kit/a", "../shared/data.bin", options.clone());
+        let second = import_kit_model("/kit/b", "../shared/data.bin", options.clone());
+        resolver.pending.borrow_mut().clear();
+        assert!(first.borrow().is_none());
+        match second.borrow_mut().take() {
+            Some(Err(ImportError::Resource(error))) => {
+                assert!(matches!(error.kind, ResourceErrorKind::Load(_)))
+            }
+            _ => panic!("expected a load error"),
+        }
+
+        // The resource is no longer in flight
+        let third = import_kit_model("/kit/a", "../shared/data.bin", options);
+        resolver.run();
+        assert_eq!(resolver.requested.borrow().len(), 2);
+        assert!(third.borrow().as_ref().unwrap().is_ok());
+    }
+
+    #[test]
+    fn test_import_cube_model_twice_with_cache() {
+        let cache = ImportCache::new();
+        let resolver = CountingResolver::default();
+        let options = ImportOptions::new()
+            .with_resolver(resolver.clone())
+            .with_cache(cache.clone());
+
+        let first = import_cube(options.clone());
+        assert_eq!(*resolver.requested.borrow(), 3);
+        assert_eq!(cache.len(), 3);
+
+        let second = import_cube(options);
+        assert_eq!(*resolver.requested.borrow(), 3);
+        assert!(Rc::ptr_eq(&first.buffers()[&0], &second.buffers()[&0]));
+        assert!(Rc::ptr_eq(&first.images()[&0], &second.images()[&0]));
+        assert!(Rc::ptr_eq(&first.images()[&1], &second.images()[&1]));
+
+        cache.clear();
+        assert!(cache.is_empty());
+        import_cube(ImportOptions::new().with_resolver(resolver.clone()));
+        assert_eq!(*resolver.requested.borrow(), 6);
+    }
+
+    #[test]
+    fn test_lazy_images_in_cache() {
+        let cache = ImportCache::new();
+        let resolver = CountingResolver::default();
+        let options = ImportOptions::new()
+            .with_resolver(resolver.clone())
+            .with_cache(cache.clone());
+
+        let lazy = import_cube(options.clone().with_lazy_images(true));
+        assert_eq!(lazy.lazy_images().len(), 2);
+        assert_eq!(*resolver.requested.borrow(), 3);
+
+        // Lazy images don't satisfy imports decoding the images, which replace them in the cache
+        let decoded = import_cube(options.clone());
+        assert_eq!(decoded.images().len(), 2);
+        assert_eq!(*resolver.requested.borrow(), 5);
+
+        let lazy = import_cube(options.with_lazy_images(true));
+        assert!(lazy.lazy_images().is_empty());
+        assert!(Rc::ptr_eq(&lazy.images()[&0], &decoded.images()[&0]));
+        assert_eq!(*resolver.requested.borrow(), 5);
+    }
+}
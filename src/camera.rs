@@ -0,0 +1,250 @@
+use crate::import::ImportedGltfModel;
+use crate::scene::scene_nodes;
+use gltf::camera::Projection;
+use three_d::{degrees, Degrees, Vec3};
+
+/// A camera placed in a scene by a node, in world space
+///
+/// Position, target and up can be passed to `three_d::Camera::new_perspective` or
+/// `three_d::Camera::new_orthographic`, with the projection converted via
+/// [`CameraProjection::three_d_perspective`](enum.CameraProjection.html#method.three_d_perspective) or
+/// [`CameraProjection::three_d_orthographic`](enum.CameraProjection.html#method.three_d_orthographic).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportedCamera {
+    /// Index of the camera from the `cameras` section of the GLTF document
+    pub camera: usize,
+    /// Index of the node referencing the camera
+    pub node: usize,
+    /// Name of the camera, if any
+    pub name: Option<String>,
+    /// Position of the camera
+    pub position: Vec3,
+    /// Point the camera looks at, one unit in front of `position`
+    pub target: Vec3,
+    /// Up direction of the camera (normalized)
+    pub up: Vec3,
+    /// Projection of the camera
+    pub projection: CameraProjection,
+}
+
+/// Projection of a GLTF camera
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CameraProjection {
+    /// Perspective projection
+    Perspective {
+        /// Vertical field of view in radians
+        yfov: f32,
+        /// Aspect ratio (width / height) of the field of view, `None` to use the aspect ratio of the viewport
+        aspect_ratio: Option<f32>,
+        /// Distance to the near clipping plane
+        znear: f32,
+        /// Distance to the far clipping plane, `None` for an infinite projection
+        zfar: Option<f32>,
+    },
+    /// Orthographic projection
+    Orthographic {
+        /// Half the horizontal size of the view
+        xmag: f32,
+        /// Half the vertical size of the view
+        ymag: f32,
+        /// Distance to the near clipping plane
+        znear: f32,
+        /// Distance to the far clipping plane
+        zfar: f32,
+    },
+}
+
+impl CameraProjection {
+    /// Ratio of the far to the near clipping plane used by
+    /// [`three_d_perspective`](#method.three_d_perspective) for infinite projections
+    ///
+    /// `three-d` has no infinite perspective projection, so a finite far plane is used instead.
+    pub const INFINITE_FAR_RATIO: f32 = 10_000.0;
+
+    /// The field of view (in degrees), aspect ratio, near and far plane of a perspective projection, as taken by
+    /// `three_d::Camera::new_perspective`
+    ///
+    /// `viewport_aspect` is used if the camera does not set an aspect ratio. Infinite projections get a far plane
+    /// at [`INFINITE_FAR_RATIO`](#associatedconstant.INFINITE_FAR_RATIO) times the near plane. `None` for
+    /// orthographic projections.
+    pub fn three_d_perspective(&self, viewport_aspect: f32) -> Option<(Degrees, f32, f32, f32)> {
+        match *self {
+            CameraProjection::Perspective {
+                yfov,
+                aspect_ratio,
+                znear,
+                zfar,
+            } => Some((
+                degrees(yfov.to_degrees()),
+                aspect_ratio.unwrap_or(viewport_aspect),
+                znear,
+                zfar.unwrap_or(znear * Self::INFINITE_FAR_RATIO),
+            )),
+            CameraProjection::Orthographic { .. } => None,
+        }
+    }
+
+    /// The width, height and depth of an orthographic projection, as taken by `three_d::Camera::new_orthographic`
+    ///
+    /// `three-d` orthographic projections start at the camera position instead of a near plane, so the depth is
+    /// the distance to the far plane, and objects in front of the near plane are not clipped. `None` for
+    /// perspective projections.
+    pub fn three_d_orthographic(&self) -> Option<(f32, f32, f32)> {
+        match *self {
+            CameraProjection::Orthographic {
+                xmag, ymag, zfar, ..
+            } => Some((2.0 * xmag, 2.0 * ymag, zfar)),
+            CameraProjection::Perspective { .. } => None,
+        }
+    }
+}
+
+impl From<&Projection<'_>> for CameraProjection {
+    fn from(projection: &Projection) -> Self {
+        match projection {
+            Projection::Perspective(perspective) => CameraProjection::Perspective {
+                yfov: perspective.yfov(),
+                aspect_ratio: perspective.aspect_ratio(),
+                znear: perspective.znear(),
+                zfar: perspective.zfar(),
+            },
+            Projection::Orthographic(orthographic) => CameraProjection::Orthographic {
+                xmag: orthographic.xmag(),
+                ymag: orthographic.ymag(),
+                znear: orthographic.znear(),
+                zfar: orthographic.zfar(),
+            },
+        }
+    }
+}
+
+impl ImportedGltfModel {
+    /// All cameras of the [default scene](#method.default_scene)
+    ///
+    /// Cameras referenced by multiple nodes are returned once per node. Empty if the document has no scenes.
+    pub fn cameras(&self) -> Vec<ImportedCamera> {
+        self.default_scene()
+            .and_then(|scene| self.cameras_in_scene(scene.index))
+            .unwrap_or_default()
+    }
+
+    /// All cameras of the scene with the given index from the `scenes` section of the GLTF document
+    ///
+    /// A GLTF camera looks along the negative Z axis of its node, with the positive Y axis up. Position, target and
+    /// up are taken from the world transformation of the node (see
+    /// [`scene_instances`](#method.scene_instances)). Cameras of nodes with a zero scale have no direction and are
+    /// left out. `None` if there is no such scene.
+    pub fn cameras_in_scene(&self, scene: usize) -> Option<Vec<ImportedCamera>> {
+        let scene = self.document().scenes().nth(scene)?;
+        Some(
+            scene_nodes(&scene)
+                .into_iter()
+                .filter_map(|scene_node| {
+                    let camera = scene_node.node.camera()?;
+                    let position = scene_node.position();
+
+                    Some(ImportedCamera {
+                        camera: camera.index(),
+                        node: scene_node.node.index(),
+                        name: camera.name().map(String::from),
+                        position,
+                        target: position + scene_node.direction(Vec3::new(0.0, 0.0, -1.0))?,
+                        up: scene_node.direction(Vec3::new(0.0, 1.0, 0.0))?,
+                        projection: CameraProjection::from(&camera.projection()),
+                    })
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::GltfImporter;
+    use three_d::InnerSpace;
+
+    #[test]
+    fn test_three_d_perspective() {
+        let projection = CameraProjection::Perspective {
+            yfov: std::f32::consts::FRAC_PI_2,
+            aspect_ratio: None,
+            znear: 0.1,
+            zfar: None,
+        };
+        let (yfov, aspect, znear, zfar) = projection.three_d_perspective(1.5).unwrap();
+        assert!((yfov.0 - 90.0).abs() < 1e-4);
+        assert_eq!(aspect, 1.5);
+        assert_eq!(znear, 0.1);
+        assert_eq!(zfar, 0.1 * CameraProjection::INFINITE_FAR_RATIO);
+        assert!(projection.three_d_orthographic().is_none());
+
+        let projection = CameraProjection::Perspective {
+            yfov: 0.8,
+            aspect_ratio: Some(2.0),
+            znear: 0.5,
+            zfar: Some(50.0),
+        };
+        let (_, aspect, _, zfar) = projection.three_d_perspective(1.5).unwrap();
+        assert_eq!((aspect, zfar), (2.0, 50.0));
+    }
+
+    #[test]
+    fn test_import_cameras() {
+        let document = r#"{
+            "asset": { "version": "2.0" },
+            "cameras": [
+                { "name": "Main", "type": "perspective", "perspective": { "yfov": 0.8, "znear": 0.1, "aspectRatio": 1.5 } },
+                { "type": "orthographic", "orthographic": { "xmag": 2, "ymag": 1, "znear": 0.5, "zfar": 10 } }
+            ],
+            "nodes": [
+                { "translation": [0, 1, 5], "camera": 0 },
+                { "translation": [0, 2, 0], "children": [2] },
+                { "rotation": [0, 0.70710677, 0, 0.70710677], "camera": 1 },
+                { "camera": 0 },
+                { "scale": [1, 0, 1], "camera": 0 }
+            ],
+            "scenes": [{ "nodes": [0, 1] }, { "nodes": [3] }, { "nodes": [4] }]
+        }"#;
+        GltfImporter::import_from_slice(document.as_bytes(), None, |imported| {
+            let result = imported.unwrap();
+            let cameras = result.cameras();
+            assert_eq!(cameras.len(), 2);
+
+            let main = &cameras[0];
+            assert_eq!(main.name.as_deref(), Some("Main"));
+            assert_eq!(main.position, Vec3::new(0.0, 1.0, 5.0));
+            assert_eq!(main.target, Vec3::new(0.0, 1.0, 4.0));
+            assert_eq!(main.up, Vec3::new(0.0, 1.0, 0.0));
+            assert_eq!(
+                main.projection,
+                CameraProjection::Perspective {
+                    yfov: 0.8,
+                    aspect_ratio: Some(1.5),
+                    znear: 0.1,
+                    zfar: None,
+                }
+            );
+
+            // Rotated by 90° around the Y axis, thus looking along the negative X axis
+            let orthographic = &cameras[1];
+            assert_eq!(orthographic.node, 2);
+            assert_eq!(orthographic.position, Vec3::new(0.0, 2.0, 0.0));
+            let direction = orthographic.target - orthographic.position;
+            assert!((direction - Vec3::new(-1.0, 0.0, 0.0)).magnitude() < 1e-6);
+            assert!(matches!(
+                orthographic.projection,
+                CameraProjection::Orthographic { xmag, ymag, .. } if xmag == 2.0 && ymag == 1.0
+            ));
+            assert_eq!(
+                orthographic.projection.three_d_orthographic(),
+                Some((4.0, 2.0, 10.0))
+            );
+            assert!(orthographic.projection.three_d_perspective(1.0).is_none());
+
+            assert_eq!(result.cameras_in_scene(1).unwrap().len(), 1);
+            assert!(result.cameras_in_scene(2).unwrap().is_empty());
+            assert!(result.cameras_in_scene(3).is_none());
+        });
+    }
+}
@@ -0,0 +1,245 @@
+use crate::limits::LimitExceeded;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Result of an import
+pub type Result<T> = std::result::Result<T, ImportError>;
+
+/// Phase of the import in which a resource failed
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ImportPhase {
+    /// Loading the buffers of the document
+    Buffers,
+    /// Loading and decoding the images of the document
+    Images,
+}
+
+/// Reason why a buffer or image could not be imported
+#[derive(Debug)]
+pub enum ResourceErrorKind {
+    /// The resource could not be loaded by the resolver
+    Load(io::Error),
+    /// The base64 data of a `data:` URI could not be decoded
+    Base64(base64::DecodeError),
+    /// The image could not be decoded
+    Decode(image::ImageError),
+    /// The image is neither PNG, JPEG nor KTX2 encoded
+    UnsupportedImageEncoding,
+    /// The URI scheme is not supported on this platform
+    UnsupportedScheme,
+    /// The URI is relative, but no base path has been provided
+    MissingBase,
+    /// The resource is not allowed by the sandbox of the import
+    Forbidden,
+    /// The resource exceeds a limit of the import
+    LimitExceeded(LimitExceeded),
+    /// The document references the binary chunk of a GLB file, but there is none
+    MissingBlob,
+    /// The image references a buffer (by index) that is not available
+    MissingBuffer(usize),
+    /// The buffer is shorter than declared in the document
+    BufferLength {
+        /// Length declared in the document
+        expected: usize,
+        /// Actual length of the buffer data
+        actual: usize,
+    },
+}
+
+impl fmt::Display for ResourceErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ResourceErrorKind::Load(err) => write!(f, "failed to load: {}", err),
+            ResourceErrorKind::Base64(err) => write!(f, "invalid base64 data: {}", err),
+            ResourceErrorKind::Decode(err) => write!(f, "failed to decode image: {}", err),
+            ResourceErrorKind::UnsupportedImageEncoding => write!(f, "unsupported image encoding"),
+            ResourceErrorKind::UnsupportedScheme => write!(f, "unsupported URI scheme"),
+            ResourceErrorKind::MissingBase => write!(f, "relative URI, but no base path given"),
+            ResourceErrorKind::Forbidden => write!(f, "not allowed by the sandbox"),
+            ResourceErrorKind::LimitExceeded(exceeded) => write!(f, "{}", exceeded),
+            ResourceErrorKind::MissingBlob => write!(f, "missing binary chunk"),
+            ResourceErrorKind::MissingBuffer(buffer) => write!(f, "missing buffer {}", buffer),
+            ResourceErrorKind::BufferLength { expected, actual } => write!(
+                f,
+                "expected {} bytes, but only {} are available",
+                expected, actual
+            ),
+        }
+    }
+}
+
+/// A buffer or image that could not be imported
+#[derive(Debug)]
+pub struct ResourceError {
+    /// Whether a buffer or an image failed
+    pub phase: ImportPhase,
+    /// Index from the `buffers` or `images` section of the GLTF document
+    pub index: usize,
+    /// The URI from the document, `None` for data in buffer views or the binary GLB chunk
+    pub uri: Option<String>,
+    /// The resolved path the resource has been loaded from, if any
+    pub path: Option<PathBuf>,
+    /// The reason for the failure
+    pub kind: ResourceErrorKind,
+}
+
+impl ResourceError {
+    pub(crate) fn new(phase: ImportPhase, index: usize, kind: ResourceErrorKind) -> Self {
+        Self {
+            phase,
+            index,
+            uri: None,
+            path: None,
+            kind,
+        }
+    }
+
+    pub(crate) fn with_uri(mut self, uri: &str) -> Self {
+        self.uri = Some(uri.to_owned());
+        self
+    }
+
+    pub(crate) fn with_path(mut self, path: PathBuf) -> Self {
+        self.path = Some(path);
+        self
+    }
+}
+
+impl fmt::Display for ResourceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.phase {
+            ImportPhase::Buffers => write!(f, "buffer {}", self.index)?,
+            ImportPhase::Images => write!(f, "image {}", self.index)?,
+        }
+        if let Some(uri) = &self.uri {
+            // Data URIs may be huge, they don't help much in error messages anyway
+            if uri.starts_with("data:") {
+                write!(f, " (data URI)")?;
+            } else {
+                write!(f, " ({})", uri)?;
+            }
+        }
+        if let Some(path) = &self.path {
+            write!(f, " at {}", path.display())?;
+        }
+        write!(f, ": {}", self.kind)
+    }
+}
+
+impl Error for ResourceError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            ResourceErrorKind::Load(err) => Some(err),
+            ResourceErrorKind::Base64(err) => Some(err),
+            ResourceErrorKind::Decode(err) => Some(err),
+            ResourceErrorKind::LimitExceeded(exceeded) => Some(exceeded),
+            _ => None,
+        }
+    }
+}
+
+/// An error shared by several resources, e.g. the load error of a file used by several images
+///
+/// Displays as the original error, which is its source.
+#[derive(Clone, Debug)]
+pub struct SharedError(pub Arc<io::Error>);
+
+impl fmt::Display for SharedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Error for SharedError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.0.as_ref())
+    }
+}
+
+/// Error of an import
+#[derive(Debug)]
+pub enum ImportError {
+    /// The `.gltf` or `.glb` file could not be loaded
+    Load {
+        /// Path of the file
+        path: PathBuf,
+        /// The error reported by the resolver
+        source: io::Error,
+    },
+    /// The GLTF document could not be parsed
+    Document(gltf::Error),
+    /// A buffer or image referenced by the document could not be imported
+    Resource(Box<ResourceError>),
+    /// The document exceeds a limit of the import, e.g. the number of images
+    LimitExceeded(LimitExceeded),
+    /// The import has been cancelled via its [`ImportHandle`](../import/struct.ImportHandle.html)
+    Cancelled,
+    /// The import stopped without a result, i.e. a [`ResourceResolver`](../resolver/trait.ResourceResolver.html)
+    /// dropped its `on_done` callback without calling it
+    Aborted,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImportError::Load { path, source } => {
+                write!(f, "failed to load {}: {}", path.display(), source)
+            }
+            ImportError::Document(err) => write!(f, "invalid GLTF document: {}", err),
+            ImportError::Resource(err) => write!(f, "failed to import {}", err),
+            ImportError::LimitExceeded(exceeded) => write!(f, "{}", exceeded),
+            ImportError::Cancelled => write!(f, "import has been cancelled"),
+            ImportError::Aborted => write!(f, "import stopped without a result"),
+        }
+    }
+}
+
+impl Error for ImportError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ImportError::Load { source, .. } => Some(source),
+            ImportError::Document(err) => Some(err),
+            ImportError::Resource(err) => Some(err.as_ref()),
+            ImportError::LimitExceeded(exceeded) => Some(exceeded),
+            ImportError::Cancelled | ImportError::Aborted => None,
+        }
+    }
+}
+
+impl From<gltf::Error> for ImportError {
+    fn from(err: gltf::Error) -> Self {
+        ImportError::Document(err)
+    }
+}
+
+impl From<ResourceError> for ImportError {
+    fn from(err: ResourceError) -> Self {
+        ImportError::Resource(Box::new(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_error_message() {
+        let error: ImportError = ResourceError::new(
+            ImportPhase::Images,
+            3,
+            ResourceErrorKind::Load(io::Error::from(io::ErrorKind::NotFound)),
+        )
+        .with_uri("textures/wood.png")
+        .with_path(PathBuf::from("models/textures/wood.png"))
+        .into();
+
+        assert_eq!(
+            error.to_string(),
+            "failed to import image 3 (textures/wood.png) at models/textures/wood.png: failed to load: entity not found"
+        );
+        assert!(error.source().is_some());
+    }
+}
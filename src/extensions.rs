@@ -0,0 +1,285 @@
+use crate::texture::{TextureSlot, TextureTransform};
+use gltf::json::Root;
+use gltf::{Document, Error, Glb};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Extensions of a GLTF document that are not supported by `gltf`, read from the JSON of the document
+///
+/// See the [extensions section](../import/struct.ImportedGltfModel.html#extensions) of `ImportedGltfModel` for
+/// when they are available.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Extensions {
+    /// KTX2 image of each texture using `KHR_texture_basisu`, by texture index
+    texture_basisu: HashMap<usize, usize>,
+    /// WebP image of each texture using `EXT_texture_webp`, by texture index
+    texture_webp: HashMap<usize, usize>,
+    /// `KHR_texture_transform` of the texture slots, by material index
+    texture_transforms: HashMap<(usize, TextureSlot), TextureTransform>,
+}
+
+impl Extensions {
+    /// Names of the extensions read by this module
+    pub(crate) const SUPPORTED: &'static [&'static str] = &[
+        "KHR_texture_basisu",
+        "EXT_texture_webp",
+        "KHR_texture_transform",
+    ];
+
+    /// Parses a `.gltf` or `.glb` file, reading the extensions as well
+    ///
+    /// Returns the range of the binary GLB chunk within `data` instead of a copy, so that the caller can keep the
+    /// loaded data instead.
+    ///
+    /// Textures using `KHR_texture_basisu` or `EXT_texture_webp` without a fallback image have no `source`, which
+    /// `gltf` requires. The image of the extension is used as `source` instead, so that such documents can be parsed
+    /// at all.
+    pub(crate) fn parse(data: &[u8]) -> gltf::Result<(Document, Option<Range<usize>>, Self)> {
+        let (json, bin) = if data.starts_with(b"glTF") {
+            let glb = Glb::from_slice(data)?;
+            // The chunks of `Glb::from_slice` are borrowed from `data`
+            let bin = glb.bin.map(|bin| {
+                let start = bin.as_ptr() as usize - data.as_ptr() as usize;
+                start..start + bin.len()
+            });
+            (glb.json, bin)
+        } else {
+            (data.into(), None)
+        };
+
+        let mut json: Value = serde_json::from_slice(&json).map_err(Error::Deserialize)?;
+        let extensions = Self::from_json(&json);
+        extensions.set_missing_sources(&mut json);
+
+        let root: Root = serde_json::from_value(json).map_err(Error::Deserialize)?;
+        let document = Document::from_json(root)?;
+        Ok((document, bin, extensions))
+    }
+
+    fn from_json(json: &Value) -> Self {
+        let sources = |name| {
+            texture_extensions(json, name)
+                .filter_map(|(index, extension)| Some((index, as_index(&extension["source"])?)))
+                .collect()
+        };
+
+        let texture_transforms = material_texture_infos(json)
+            .filter_map(|(material, slot, info)| {
+                let transform = info["extensions"].get("KHR_texture_transform")?;
+                Some(((material, slot), texture_transform(transform)))
+            })
+            .collect();
+
+        Self {
+            texture_basisu: sources("KHR_texture_basisu"),
+            texture_webp: sources("EXT_texture_webp"),
+            texture_transforms,
+        }
+    }
+
+    fn set_missing_sources(&self, json: &mut Value) {
+        let textures = match json.get_mut("textures").and_then(Value::as_array_mut) {
+            Some(textures) => textures,
+            None => return,
+        };
+        for (index, texture) in textures.iter_mut().enumerate() {
+            let source = self
+                .texture_basisu(index)
+                .or_else(|| self.texture_webp(index));
+            if let (Some(source), Some(texture)) = (source, texture.as_object_mut()) {
+                texture
+                    .entry("source")
+                    .or_insert_with(|| Value::from(source));
+            }
+        }
+    }
+
+    /// The KTX2 image of the texture with the given index
+    pub(crate) fn texture_basisu(&self, texture: usize) -> Option<usize> {
+        self.texture_basisu.get(&texture).copied()
+    }
+
+    /// The WebP image of the texture with the given index
+    pub(crate) fn texture_webp(&self, texture: usize) -> Option<usize> {
+        self.texture_webp.get(&texture).copied()
+    }
+
+    /// The `KHR_texture_transform` of the given texture slot of the material with the given index
+    pub(crate) fn texture_transform(
+        &self,
+        material: usize,
+        slot: TextureSlot,
+    ) -> Option<TextureTransform> {
+        self.texture_transforms.get(&(material, slot)).copied()
+    }
+
+    /// Whether the image with the given index is the WebP image of a texture
+    pub(crate) fn is_webp_image(&self, image: usize) -> bool {
+        self.texture_webp.values().any(|&webp| webp == image)
+    }
+}
+
+/// The extension with the given name of each texture having it, with the index of the texture
+fn texture_extensions<'a>(
+    json: &'a Value,
+    name: &'a str,
+) -> impl Iterator<Item = (usize, &'a Value)> + 'a {
+    json["textures"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .filter_map(move |(index, texture)| {
+            texture["extensions"]
+                .get(name)
+                .map(|extension| (index, extension))
+        })
+}
+
+/// The texture infos of all materials, with the index of the material and the slot they are used for
+fn material_texture_infos(json: &Value) -> impl Iterator<Item = (usize, TextureSlot, &Value)> {
+    json["materials"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .flat_map(|(index, material)| {
+            let pbr = &material["pbrMetallicRoughness"];
+            vec![
+                (TextureSlot::BaseColor, &pbr["baseColorTexture"]),
+                (
+                    TextureSlot::MetallicRoughness,
+                    &pbr["metallicRoughnessTexture"],
+                ),
+                (TextureSlot::Normal, &material["normalTexture"]),
+                (TextureSlot::Occlusion, &material["occlusionTexture"]),
+                (TextureSlot::Emissive, &material["emissiveTexture"]),
+            ]
+            .into_iter()
+            .filter(|(_, info)| info.is_object())
+            .map(move |(slot, info)| (index, slot, info))
+        })
+}
+
+fn texture_transform(extension: &Value) -> TextureTransform {
+    let default = TextureTransform::default();
+    TextureTransform {
+        offset: as_vec2(&extension["offset"]).unwrap_or(default.offset),
+        rotation: extension["rotation"]
+            .as_f64()
+            .map_or(default.rotation, |rotation| rotation as f32),
+        scale: as_vec2(&extension["scale"]).unwrap_or(default.scale),
+        tex_coord: extension["texCoord"]
+            .as_u64()
+            .map(|tex_coord| tex_coord as u32),
+    }
+}
+
+fn as_index(value: &Value) -> Option<usize> {
+    value.as_u64().map(|index| index as usize)
+}
+
+fn as_vec2(value: &Value) -> Option<[f32; 2]> {
+    match value.as_array()?.as_slice() {
+        [x, y] => Some([x.as_f64()? as f32, y.as_f64()? as f32]),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_texture_basisu() {
+        let (document, _, extensions) = Extensions::parse(
+            br#"{
+                "asset": { "version": "2.0" },
+                "textures": [
+                    { "source": 0 },
+                    { "source": 0, "extensions": { "KHR_texture_basisu": { "source": 1 } } },
+                    { "extensions": { "KHR_texture_basisu": { "source": 1 } } }
+                ],
+                "images": [{ "uri": "fallback.png" }, { "uri": "compressed.ktx2" }]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(extensions.texture_basisu(0), None);
+        assert_eq!(extensions.texture_basisu(1), Some(1));
+        assert_eq!(extensions.texture_basisu(2), Some(1));
+
+        let sources: Vec<_> = document
+            .textures()
+            .map(|texture| texture.source().index())
+            .collect();
+        assert_eq!(sources, vec![0, 0, 1]);
+
+        assert!(Extensions::parse(b"not json").is_err());
+    }
+
+    #[test]
+    fn test_read_texture_webp() {
+        let (_, _, extensions) = Extensions::parse(
+            br#"{
+                "asset": { "version": "2.0" },
+                "textures": [
+                    { "source": 0 },
+                    { "source": 0, "extensions": { "EXT_texture_webp": { "source": 1 } } }
+                ],
+                "images": [{ "uri": "fallback.png" }, { "uri": "image.webp" }]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(extensions.texture_webp(0), None);
+        assert_eq!(extensions.texture_webp(1), Some(1));
+        assert!(extensions.is_webp_image(1));
+        assert!(!extensions.is_webp_image(0));
+    }
+
+    #[test]
+    fn test_read_texture_transforms() {
+        let (_, _, extensions) = Extensions::parse(
+            br#"{
+                "asset": { "version": "2.0" },
+                "textures": [{ "source": 0 }],
+                "images": [{ "uri": "image.png" }],
+                "materials": [{
+                    "pbrMetallicRoughness": {
+                        "baseColorTexture": {
+                            "index": 0,
+                            "extensions": { "KHR_texture_transform": { "offset": [0.5, 0], "scale": [2, 2] } }
+                        }
+                    },
+                    "normalTexture": {
+                        "index": 0,
+                        "extensions": { "KHR_texture_transform": { "rotation": 1.5, "texCoord": 1 } }
+                    },
+                    "emissiveTexture": { "index": 0 }
+                }]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            extensions.texture_transform(0, TextureSlot::BaseColor),
+            Some(TextureTransform {
+                offset: [0.5, 0.0],
+                scale: [2.0, 2.0],
+                ..TextureTransform::default()
+            })
+        );
+        assert_eq!(
+            extensions.texture_transform(0, TextureSlot::Normal),
+            Some(TextureTransform {
+                rotation: 1.5,
+                tex_coord: Some(1),
+                ..TextureTransform::default()
+            })
+        );
+        assert_eq!(extensions.texture_transform(0, TextureSlot::Emissive), None);
+        assert_eq!(
+            extensions.texture_transform(1, TextureSlot::BaseColor),
+            None
+        );
+    }
+}
@@ -1,18 +1,36 @@
+use crate::cache::{ImportCache, SharedImage};
+use crate::error::{
+    ImportError, ImportPhase, ResourceError, ResourceErrorKind, Result, SharedError,
+};
+use crate::extensions::Extensions;
+use crate::ktx2::{self, Ktx2Header, Ktx2Image};
+use crate::limits::ImportLimits;
+use crate::mesh;
+use crate::progress::{FinishedResource, ImportProgress, ProgressCallback, ProgressReporter};
+use crate::resolver::{
+    LoadOptions, LoadedResources, LoaderResolver, Resource, ResourceData, ResourceResolver,
+};
+use crate::sandbox::Sandbox;
 use base64;
+use futures_channel::oneshot;
 use gltf::buffer;
 use gltf::image as gltf_image;
-use gltf::{Document, Error, Gltf, Result};
+use gltf::{Document, Gltf, Texture};
 use image::ImageFormat::{Jpeg, Png};
-use image::{DynamicImage, ImageFormat};
+use image::{DynamicImage, GenericImageView, ImageFormat};
+use once_cell::unsync::OnceCell;
+use std::borrow::Cow;
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::io;
 use std::path::{Path, PathBuf};
-use three_d::Loader;
+use std::rc::Rc;
+use std::sync::Arc;
 
-#[cfg(not(target_arch = "wasm32"))]
-use three_d::IOError;
-
-pub type LoadedImages = HashMap<usize, DynamicImage>;
-pub type LoadedBuffers = HashMap<usize, buffer::Data>;
+pub type LoadedImages = HashMap<usize, Rc<DynamicImage>>;
+pub type LazyImages = HashMap<usize, Rc<LazyImage>>;
+pub type CompressedImages = HashMap<usize, Rc<Ktx2Image>>;
+pub type LoadedBuffers = HashMap<usize, Rc<ResourceData>>;
 
 /// Importer for GLTF models
 ///
@@ -29,32 +47,110 @@ pub type LoadedBuffers = HashMap<usize, buffer::Data>;
 ///     let result = imported.unwrap();
 ///     assert_eq!(result.buffers().len(), 1);
 ///     assert_eq!(result.images().len(), 8);
-/// })
+/// });
 /// ```
 pub struct GltfImporter {}
 
 /// Imported GLTF model
+///
+/// # Extensions
+///
+/// `gltf` drops extensions it does not support while parsing a document. Some of them (`KHR_texture_basisu`,
+/// `EXT_texture_webp`, `KHR_texture_transform`) are thus read from the raw JSON of the document, which is only
+/// available if the importer has been given it, i.e. via
+/// [`GltfImporter::import_from_path`](struct.GltfImporter.html#method.import_from_path),
+/// [`GltfImporter::import_from_slice`](struct.GltfImporter.html#method.import_from_slice) or their `async`
+/// equivalents. For documents imported via [`GltfImporter::import`](struct.GltfImporter.html#method.import) or
+/// [`GltfImporter::import_async`](struct.GltfImporter.html#method.import_async) these extensions are ignored, which
+/// is reported by [`unavailable_extensions`](#method.unavailable_extensions).
 #[derive(Clone, Debug)]
 pub struct ImportedGltfModel {
     /// Imported image data
     images: LoadedImages,
+    /// Imported, but not yet decoded image data
+    lazy_images: LazyImages,
+    /// Imported KTX2 images
+    compressed_images: CompressedImages,
     /// Imported buffer data
     buffers: LoadedBuffers,
     /// The parsed GLTF document
     document: Document,
+    /// Images that failed to import in lenient mode
+    warnings: Rc<Vec<ResourceError>>,
+    /// Extensions read from the JSON of the document
+    extensions: Rc<Extensions>,
+    /// Extensions used by the document that could not be read
+    unavailable_extensions: Rc<Vec<String>>,
 }
 
 impl ImportedGltfModel {
     /// Imported image data
     ///
     /// Keys of the hashmap corresponds to the indexes from the `images` section of the GLTF document
+    ///
+    /// Images imported lazily (see [`ImportOptions::with_lazy_images`](struct.ImportOptions.html#method.with_lazy_images))
+    /// are not included, use [`image`](#method.image) to access any image.
     pub fn images(&self) -> &LoadedImages {
         &self.images
     }
 
+    /// Imported images that are kept encoded until they are accessed
+    ///
+    /// Keys of the hashmap corresponds to the indexes from the `images` section of the GLTF document
+    pub fn lazy_images(&self) -> &LazyImages {
+        &self.lazy_images
+    }
+
+    /// Imported KTX2 images (e.g. of the `KHR_texture_basisu` extension), kept compressed for GPU upload
+    ///
+    /// Keys of the hashmap corresponds to the indexes from the `images` section of the GLTF document.
+    /// Use [`texture_compressed_image`](#method.texture_compressed_image) to get the image of a texture.
+    pub fn compressed_images(&self) -> &CompressedImages {
+        &self.compressed_images
+    }
+
+    /// The KTX2 image of a texture using the `KHR_texture_basisu` extension
+    ///
+    /// The `source` of such textures is their PNG or JPEG fallback image, or the KTX2 image itself if there is no
+    /// fallback. The extension is only known for some imports, see [extensions](#extensions).
+    pub fn texture_compressed_image(&self, texture: &Texture) -> Option<&Ktx2Image> {
+        let index = self.extensions.texture_basisu(texture.index())?;
+        self.compressed_images.get(&index).map(Rc::as_ref)
+    }
+
+    /// The decoded image with the given index from the `images` section of the GLTF document
+    ///
+    /// Lazily imported images are decoded on the first access. Returns `None` if there is no such image, if it
+    /// could not be decoded (see [`LazyImage::decode`](struct.LazyImage.html#method.decode) for the error), or if it
+    /// is a [compressed image](#method.compressed_images).
+    pub fn image(&self, index: usize) -> Option<&DynamicImage> {
+        match self.lazy_images.get(&index) {
+            Some(image) => image.decode().ok(),
+            None => self.images.get(&index).map(Rc::as_ref),
+        }
+    }
+
+    /// The decoded image of the given texture
+    ///
+    /// With the `webp` feature, textures using the `EXT_texture_webp` extension use their WebP image. Otherwise
+    /// (or if the WebP image is not available) they use their fallback `source` image. The extension is only known
+    /// for some imports, see [extensions](#extensions).
+    pub fn texture_image(&self, texture: &Texture) -> Option<&DynamicImage> {
+        #[cfg(feature = "webp")]
+        {
+            let webp = self.extensions.texture_webp(texture.index());
+            if let Some(image) = webp.and_then(|index| self.image(index)) {
+                return Some(image);
+            }
+        }
+
+        self.image(texture.source().index())
+    }
+
     /// Imported buffer data
     ///
-    /// Keys of the hashmap corresponds to the indexes from the `buffers` section of the GLTF document
+    /// Keys of the hashmap corresponds to the indexes from the `buffers` section of the GLTF document.
+    /// The data is used as loaded (or embedded), without copying or padding it.
     pub fn buffers(&self) -> &LoadedBuffers {
         &self.buffers
     }
@@ -63,29 +159,548 @@ impl ImportedGltfModel {
     pub fn document(&self) -> &Document {
         &self.document
     }
+
+    /// Extensions read from the JSON of the document
+    pub(crate) fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    /// Images that failed to load or decode, but did not fail the import
+    ///
+    /// Only populated if the import has been lenient about failing images, see
+    /// [`ImportOptions::with_image_failures`](struct.ImportOptions.html#method.with_image_failures).
+    pub fn warnings(&self) -> &[ResourceError] {
+        &self.warnings
+    }
+
+    /// Extensions used by the document that have been ignored, since the importer has not been given the raw
+    /// document (see [extensions](#extensions))
+    pub fn unavailable_extensions(&self) -> &[String] {
+        &self.unavailable_extensions
+    }
+}
+
+/// An image kept in its encoded form, decoded on first access
+#[derive(Clone, Debug)]
+pub struct LazyImage {
+    data: Vec<u8>,
+    format: ImageFormat,
+    decoded: OnceCell<DynamicImage>,
+}
+
+impl LazyImage {
+    /// The encoded image data
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// The format of the encoded image data
+    pub fn format(&self) -> ImageFormat {
+        self.format
+    }
+
+    /// Whether the image has already been decoded
+    pub fn is_decoded(&self) -> bool {
+        self.decoded.get().is_some()
+    }
+
+    /// Decodes the image, or returns the already decoded image
+    ///
+    /// Failed decoding is not cached, i.e. it is retried on the next call.
+    pub fn decode(&self) -> image::ImageResult<&DynamicImage> {
+        self.decoded
+            .get_or_try_init(|| image::load_from_memory_with_format(&self.data, self.format))
+    }
+}
+
+/// Options for importing GLTF models
+///
+/// ```rust
+/// use gltf::Gltf;
+/// use std::path::PathBuf;
+/// use three_d_gltf_import::import::{GltfImporter, ImportOptions};
+/// use three_d_gltf_import::resolver::LoaderResolver;
+///
+/// let base = PathBuf::from("./sample_models/2.0/Cube/glTF");
+/// let gltf = Gltf::open(base.join("Cube.gltf")).unwrap();
+/// let options = ImportOptions::new().with_resolver(LoaderResolver);
+/// GltfImporter::import_with_options(gltf, Some(base), options, |imported| {
+///     assert_eq!(imported.unwrap().images().len(), 2);
+/// });
+/// ```
+#[derive(Clone)]
+pub struct ImportOptions {
+    /// Resolver used to load external buffers and images
+    resolver: Rc<dyn ResourceResolver>,
+    /// Callback receiving progress updates
+    progress: Option<ProgressCallback>,
+    /// How images that fail to load or decode are handled
+    image_failures: ImageFailurePolicy,
+    /// Restricts which resources may be loaded
+    sandbox: Sandbox,
+    /// Limits for the loaded resources
+    limits: ImportLimits,
+    /// Whether images are kept encoded until they are accessed
+    lazy_images: bool,
+    /// Cache shared with other imports
+    cache: Option<ImportCache>,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            resolver: Rc::new(LoaderResolver),
+            progress: None,
+            image_failures: ImageFailurePolicy::Fail,
+            sandbox: Sandbox::default(),
+            limits: ImportLimits::default(),
+            lazy_images: false,
+            cache: None,
+        }
+    }
+}
+
+impl ImportOptions {
+    /// Default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the resolver used to load external buffers and images
+    ///
+    /// Defaults to [`LoaderResolver`](../resolver/struct.LoaderResolver.html), which uses `three-d`'s Loader.
+    pub fn with_resolver<R: 'static + ResourceResolver>(mut self, resolver: R) -> Self {
+        self.resolver = Rc::new(resolver);
+        self
+    }
+
+    /// Sets a callback that receives progress updates while buffers and images are loaded
+    ///
+    /// ```rust
+    /// use three_d_gltf_import::import::ImportOptions;
+    /// use three_d_gltf_import::progress::ImportProgress;
+    ///
+    /// let options = ImportOptions::new().with_progress(|progress: &ImportProgress| {
+    ///     println!("loaded {} of {} resources", progress.finished(), progress.total());
+    /// });
+    /// ```
+    pub fn with_progress<F: 'static + Fn(&ImportProgress)>(mut self, callback: F) -> Self {
+        self.progress = Some(Rc::new(callback));
+        self
+    }
+
+    /// Sets how images that fail to load or decode (e.g. missing files or unsupported encodings) are handled
+    ///
+    /// Defaults to [`ImageFailurePolicy::Fail`](enum.ImageFailurePolicy.html#variant.Fail), failing the whole import.
+    /// With any other policy the import continues, and the failures are available via
+    /// [`ImportedGltfModel::warnings`](struct.ImportedGltfModel.html#method.warnings).
+    ///
+    /// ```rust
+    /// use three_d_gltf_import::import::{ImageFailurePolicy, ImportOptions};
+    ///
+    /// let options = ImportOptions::new().with_image_failures(ImageFailurePolicy::Placeholder);
+    /// ```
+    pub fn with_image_failures(mut self, policy: ImageFailurePolicy) -> Self {
+        self.image_failures = policy;
+        self
+    }
+
+    /// Sets the sandbox restricting which buffers and images may be loaded
+    ///
+    /// Defaults to [`Sandbox::unrestricted`](../sandbox/struct.Sandbox.html#method.unrestricted).
+    /// Use [`Sandbox::base_dir`](../sandbox/struct.Sandbox.html#method.base_dir) for untrusted models.
+    pub fn with_sandbox(mut self, sandbox: Sandbox) -> Self {
+        self.sandbox = sandbox;
+        self
+    }
+
+    /// Sets limits for the number and size of buffers and images
+    ///
+    /// Defaults to no limits, see [`ImportLimits`](../limits/struct.ImportLimits.html).
+    pub fn with_limits(mut self, limits: ImportLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets whether images are kept in their encoded form and only decoded when they are first accessed
+    ///
+    /// Lazily imported images are available via [`ImportedGltfModel::image`](struct.ImportedGltfModel.html#method.image)
+    /// and [`ImportedGltfModel::lazy_images`](struct.ImportedGltfModel.html#method.lazy_images), but not via
+    /// `ImportedGltfModel::images`. Their format (and limits) are still checked during the import, errors in the
+    /// image data itself only show when decoding.
+    pub fn with_lazy_images(mut self, lazy: bool) -> Self {
+        self.lazy_images = lazy;
+        self
+    }
+
+    /// Sets a cache for external buffers and images, shared with other imports using the same cache
+    ///
+    /// Defaults to no cache, see [`ImportCache`](../cache/struct.ImportCache.html). Images that have been cached
+    /// decoded are also imported decoded when keeping images encoded (see
+    /// [`with_lazy_images`](#method.with_lazy_images)).
+    pub fn with_cache(mut self, cache: ImportCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Loads `resources` via the resolver, through the cache if set
+    fn load_resources(
+        &self,
+        resources: Vec<Resource>,
+        options: LoadOptions,
+        on_done: Box<dyn FnOnce(LoadedResources)>,
+    ) {
+        match &self.cache {
+            Some(cache) => cache.load(self.resolver.as_ref(), resources, options, on_done),
+            None => self.resolver.load_with_options(resources, options, on_done),
+        }
+    }
+}
+
+/// How images that fail to load or decode are handled during an import
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ImageFailurePolicy {
+    /// Fail the whole import
+    Fail,
+    /// Replace the image by a 1x1 opaque white placeholder, so materials still get a (neutral) texture
+    Placeholder,
+    /// Leave the image out of the imported images, materials are converted without the respective texture
+    Omit,
+}
+
+fn placeholder_image() -> DynamicImage {
+    DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(
+        1,
+        1,
+        image::Rgba([255, 255, 255, 255]),
+    ))
+}
+
+/// Handle to a running import
+///
+/// Returned by the `import*` functions of [`GltfImporter`](struct.GltfImporter.html), it allows to cancel the import.
+/// After cancelling, no further resources are loaded and no images are decoded, the `on_done` callback is called with
+/// [`ImportError::Cancelled`](../error/enum.ImportError.html#variant.Cancelled) instead.
+///
+/// ```rust
+/// use gltf::Gltf;
+/// use std::path::PathBuf;
+/// use three_d_gltf_import::import::GltfImporter;
+///
+/// let base = PathBuf::from("./sample_models/2.0/Cube/glTF");
+/// let gltf = Gltf::open(base.join("Cube.gltf")).unwrap();
+/// let handle = GltfImporter::import(gltf, Some(base), |imported| {
+///     // process imported document
+/// });
+///
+/// // e.g. when the user navigates to another model
+/// handle.cancel();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ImportHandle {
+    cancelled: Rc<Cell<bool>>,
+}
+
+impl ImportHandle {
+    /// Cancels the import
+    pub fn cancel(&self) {
+        self.cancelled.set(true);
+    }
+
+    /// Whether the import has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.get()
+    }
+}
+
+/// Errors of the resources loaded for the images, handed out by value
+///
+/// An error of a resource used by several images is shared between them, each of them getting an `io::Error` with
+/// the same kind, whose source is the original error.
+struct LoadErrors {
+    errors: HashMap<Resource, io::Error>,
+    shared: HashMap<Resource, Arc<io::Error>>,
+    uses: HashMap<Resource, usize>,
+}
+
+impl LoadErrors {
+    fn new(images: &[ImageImport]) -> Self {
+        let mut uses = HashMap::new();
+        for image in images {
+            if let ImageImport::NeedsLoading { resource, .. } = image {
+                *uses.entry(resource.clone()).or_insert(0) += 1;
+            }
+        }
+        Self {
+            errors: HashMap::new(),
+            shared: HashMap::new(),
+            uses,
+        }
+    }
+
+    fn insert(&mut self, resource: Resource, err: io::Error) {
+        self.errors.insert(resource, err);
+    }
+
+    fn take(&mut self, resource: &Resource) -> Option<io::Error> {
+        if self.uses.get(resource).copied().unwrap_or(0) <= 1 {
+            return self.errors.remove(resource);
+        }
+
+        let shared = match self.shared.get(resource) {
+            Some(shared) => shared.clone(),
+            None => {
+                let shared = Arc::new(self.errors.remove(resource)?);
+                self.shared.insert(resource.clone(), shared.clone());
+                shared
+            }
+        };
+        Some(io::Error::new(shared.kind(), SharedError(shared)))
+    }
+}
+
+/// Cancels the import when dropped, e.g. together with the future of an async import
+struct CancelOnDrop(ImportHandle);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+/// State shared by all steps of a single import
+struct ImportContext {
+    options: ImportOptions,
+    progress: Rc<ProgressReporter>,
+    handle: ImportHandle,
+    /// Estimated memory of the images decoded so far, see `ImportLimits::with_max_image_bytes`
+    image_bytes: Cell<usize>,
+    extensions: Rc<Extensions>,
 }
 
 enum ImageImport {
-    Loaded {
+    Embedded {
+        index: usize,
+        uri: String,
+        data: Vec<u8>,
+        mime_type: Option<String>,
+    },
+    View {
         index: usize,
-        data: DynamicImage,
+        buffer: usize,
+        begin: usize,
+        end: usize,
+        mime_type: String,
     },
     NeedsLoading {
         index: usize,
-        path: PathBuf,
+        uri: String,
+        resource: Resource,
         mime_type: Option<String>,
     },
+    Failed(ResourceError),
+}
+
+/// Result of a single image, the error is boxed since it is rather large
+type ImageResult<T> = std::result::Result<T, Box<ResourceError>>;
+
+/// Where the data of an image came from, for error reporting
+struct ImageSource {
+    index: usize,
+    uri: Option<String>,
+    /// The resource the image has been loaded from, if any
+    resource: Option<Resource>,
+}
+
+impl ImageSource {
+    fn error(self, kind: ResourceErrorKind) -> Box<ResourceError> {
+        Box::new(ResourceError {
+            uri: self.uri,
+            path: self.resource.as_ref().map(Resource::path),
+            ..ResourceError::new(ImportPhase::Images, self.index, kind)
+        })
+    }
+
+    /// Checks an image taken from the cache against the limits of this import
+    fn cached(
+        self,
+        (image, bytes): (SharedImage, usize),
+        context: &ImportContext,
+    ) -> ImageResult<(usize, SharedImage, usize)> {
+        let checked = match &image {
+            SharedImage::Decoded(image) => {
+                let (width, height) = image.dimensions();
+                GltfImporter::check_image_limits(width, height, context)
+            }
+            SharedImage::Lazy(image) => {
+                GltfImporter::check_encoded_image(image.data(), image.format(), context)
+            }
+            SharedImage::Compressed(image) => {
+                let header = image.header();
+                GltfImporter::check_image_limits(header.width, header.height.max(1), context)
+            }
+        };
+
+        match checked {
+            Ok(()) => Ok((self.index, image, bytes)),
+            Err(kind) => Err(self.error(kind)),
+        }
+    }
+
+    /// Checks the format and limits of the encoded `data`, so it can be decoded
+    fn encoded<'a>(
+        self,
+        data: Cow<'a, [u8]>,
+        mime_type: Option<&str>,
+        context: &ImportContext,
+    ) -> ImageResult<EncodedImage<'a>> {
+        match GltfImporter::encoded_format(&data, mime_type, context) {
+            Ok(format) => Ok(EncodedImage {
+                source: self,
+                data,
+                format,
+            }),
+            Err(kind) => Err(self.error(kind)),
+        }
+    }
+}
+
+/// Encoded data of an image, ready to be decoded
+struct EncodedImage<'a> {
+    source: ImageSource,
+    data: Cow<'a, [u8]>,
+    format: EncodedFormat,
+}
+
+/// Format of an encoded image
+enum EncodedFormat {
+    /// A format decoded via the `image` crate
+    Image(ImageFormat),
+    /// A KTX2 image, which is kept compressed
+    Ktx2(Ktx2Header),
+}
+
+impl EncodedImage<'_> {
+    fn decode(self) -> ImageResult<(ImageSource, ImportedImage, usize)> {
+        let length = self.data.len();
+        match self.format {
+            EncodedFormat::Image(format) => {
+                match image::load_from_memory_with_format(&self.data, format) {
+                    Ok(image) => Ok((self.source, ImportedImage::Decoded(image), length)),
+                    Err(err) => Err(self.source.error(ResourceErrorKind::Decode(err))),
+                }
+            }
+            EncodedFormat::Ktx2(header) => {
+                let image = Ktx2Image::new(self.data.into_owned(), header);
+                Ok((self.source, ImportedImage::Compressed(image), length))
+            }
+        }
+    }
+
+    fn into_lazy(self) -> (ImageSource, ImportedImage, usize) {
+        let length = self.data.len();
+        let image = match self.format {
+            EncodedFormat::Image(format) => ImportedImage::Lazy(LazyImage {
+                data: self.data.into_owned(),
+                format,
+                decoded: OnceCell::new(),
+            }),
+            EncodedFormat::Ktx2(header) => {
+                ImportedImage::Compressed(Ktx2Image::new(self.data.into_owned(), header))
+            }
+        };
+
+        (self.source, image, length)
+    }
+}
+
+enum ImportedImage {
+    Decoded(DynamicImage),
+    Lazy(LazyImage),
+    Compressed(Ktx2Image),
+}
+
+impl ImportedImage {
+    fn into_shared(self) -> SharedImage {
+        match self {
+            ImportedImage::Decoded(image) => SharedImage::Decoded(Rc::new(image)),
+            ImportedImage::Lazy(image) => SharedImage::Lazy(Rc::new(image)),
+            ImportedImage::Compressed(image) => SharedImage::Compressed(Rc::new(image)),
+        }
+    }
+}
+
+/// Images of an import, as passed on from `load_image_data`
+struct ImportedImages {
+    images: LoadedImages,
+    lazy_images: LazyImages,
+    compressed_images: CompressedImages,
+    warnings: Vec<ResourceError>,
+}
+
+type DecodedImage = ImageResult<(ImageSource, ImportedImage, usize)>;
+
+/// Decodes the images in parallel, passing each of them to `on_decoded` on the calling thread once it is decoded
+///
+/// No more images are decoded once `on_decoded` returns `false`.
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+fn decode_images<F>(images: Vec<ImageResult<EncodedImage>>, mut on_decoded: F)
+where
+    F: FnMut(DecodedImage) -> bool,
+{
+    use rayon::prelude::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc;
+
+    let stopped = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+    std::thread::scope(|scope| {
+        let stopped = &stopped;
+        scope.spawn(move || {
+            images
+                .into_par_iter()
+                .for_each_with(sender, |sender, image| {
+                    if !stopped.load(Ordering::Relaxed) {
+                        // The receiver is only gone once decoding has been stopped
+                        let _ = sender.send(image.and_then(EncodedImage::decode));
+                    }
+                })
+        });
+
+        for image in receiver {
+            if !on_decoded(image) {
+                stopped.store(true, Ordering::Relaxed);
+                break;
+            }
+        }
+    });
+}
+
+/// Decodes the images one after another, passing each of them to `on_decoded` once it is decoded
+///
+/// No more images are decoded once `on_decoded` returns `false`.
+#[cfg(not(all(feature = "parallel", not(target_arch = "wasm32"))))]
+fn decode_images<F>(images: Vec<ImageResult<EncodedImage>>, mut on_decoded: F)
+where
+    F: FnMut(DecodedImage) -> bool,
+{
+    for image in images {
+        if !on_decoded(image.and_then(EncodedImage::decode)) {
+            break;
+        }
+    }
 }
 
 enum BufferImport {
     Loaded {
         index: usize,
-        data: Vec<u8>,
+        data: ResourceData,
         length: usize,
     },
     NeedsLoading {
         index: usize,
-        path: PathBuf,
+        uri: String,
+        resource: Resource,
         length: usize,
     },
 }
@@ -94,37 +709,151 @@ impl GltfImporter {
     /// Imports a provided gltf document
     ///
     /// If any relative, external references to buffers or images exist in the document, `base` needs to be provided
-    /// with the base path (i.e. the path that file paths in the document are relative to)
+    /// with the base path (i.e. the path that file paths in the document are relative to).
+    /// Documents with only embedded (`data:`), absolute (`file:`) or `http[s]://` references can be imported without it.
     ///
     /// The importing happens asynchronously since it may need to download external files etc...
     /// Thus a `on_done` callback will be called with the imported document, or an error in case the document couldnt be imported
     ///
     /// Async handling thus is similar to [`three-d`'s Loader](https://docs.rs/three-d/latest/three_d/io/struct.Loader.html#method.load)
     ///
+    /// The returned [`ImportHandle`](struct.ImportHandle.html) can be used to cancel the import.
+    ///
+    /// Extensions `gltf` does not support (e.g. `EXT_texture_webp` and `KHR_texture_transform`) are ignored, see
+    /// [extensions](struct.ImportedGltfModel.html#extensions). Use [`import_from_path`](#method.import_from_path) or
+    /// [`import_from_slice`](#method.import_from_slice) for documents using them.
+    ///
     /// ```rust
     /// use three_d_gltf_import::import::GltfImporter;
     /// GltfImporter::import(gltf, Some(base), |imported| {
     ///     // process imported document
-    /// })
+    /// });
     /// ```
-    pub fn import<F>(Gltf { document, blob }: Gltf, base: Option<PathBuf>, on_done: F)
+    pub fn import<F>(gltf: Gltf, base: Option<PathBuf>, on_done: F) -> ImportHandle
+    where
+        F: 'static + FnOnce(Result<ImportedGltfModel>),
+    {
+        Self::import_with_options(gltf, base, ImportOptions::default(), on_done)
+    }
+
+    /// Imports a provided gltf document using the given `options`
+    ///
+    /// See [`import`](#method.import) for details
+    pub fn import_with_options<F>(
+        gltf: Gltf,
+        base: Option<PathBuf>,
+        options: ImportOptions,
+        on_done: F,
+    ) -> ImportHandle
+    where
+        F: 'static + FnOnce(Result<ImportedGltfModel>),
+    {
+        let handle = ImportHandle::default();
+        let blob = gltf.blob.map(ResourceData::from);
+        Self::import_with_handle(
+            gltf.document,
+            blob,
+            None,
+            base,
+            options,
+            handle.clone(),
+            on_done,
+        );
+        handle
+    }
+
+    /// Parses and imports the `.gltf` or `.glb` file `data`
+    ///
+    /// Unlike [`import`](#method.import), this reads the [extensions](struct.ImportedGltfModel.html#extensions)
+    /// `gltf` does not support. See there for the meaning of `base`.
+    pub fn import_from_slice<F>(data: &[u8], base: Option<PathBuf>, on_done: F) -> ImportHandle
+    where
+        F: 'static + FnOnce(Result<ImportedGltfModel>),
+    {
+        Self::import_from_slice_with_options(data, base, ImportOptions::default(), on_done)
+    }
+
+    /// Parses and imports the `.gltf` or `.glb` file `data` using the given `options`
+    ///
+    /// See [`import_from_slice`](#method.import_from_slice) for details
+    pub fn import_from_slice_with_options<F>(
+        data: &[u8],
+        base: Option<PathBuf>,
+        options: ImportOptions,
+        on_done: F,
+    ) -> ImportHandle
     where
         F: 'static + FnOnce(Result<ImportedGltfModel>),
     {
+        let handle = ImportHandle::default();
+        match Extensions::parse(data) {
+            Ok((document, bin, extensions)) => Self::import_with_handle(
+                document,
+                bin.map(|bin| ResourceData::Owned(data[bin].to_vec())),
+                Some(extensions),
+                base,
+                options,
+                handle.clone(),
+                on_done,
+            ),
+            Err(e) => on_done(Err(e.into())),
+        }
+        handle
+    }
+
+    /// Imports the document with its binary GLB chunk `blob`, `extensions` being `None` if the raw document is not
+    /// available
+    fn import_with_handle<F>(
+        document: Document,
+        blob: Option<ResourceData>,
+        extensions: Option<Extensions>,
+        base: Option<PathBuf>,
+        options: ImportOptions,
+        handle: ImportHandle,
+        on_done: F,
+    ) where
+        F: 'static + FnOnce(Result<ImportedGltfModel>),
+    {
+        if let Err(exceeded) = options.limits.check_document(&document) {
+            return on_done(Err(ImportError::LimitExceeded(exceeded)));
+        }
+
+        let unavailable_extensions = match &extensions {
+            Some(_) => Vec::new(),
+            None => document
+                .extensions_used()
+                .filter(|name| Extensions::SUPPORTED.contains(name))
+                .map(str::to_owned)
+                .collect(),
+        };
+        let extensions = Rc::new(extensions.unwrap_or_default());
+
+        let context = Rc::new(ImportContext {
+            progress: ProgressReporter::new(options.progress.clone(), &document),
+            options,
+            handle,
+            image_bytes: Cell::new(0),
+            extensions: extensions.clone(),
+        });
         Self::load_buffer_data(
             document,
             base.clone().as_deref(),
             blob,
+            context.clone(),
             move |buffer_data, document| {
                 let buffers = match buffer_data {
                     Ok(data) => data,
                     Err(e) => return on_done(Err(e)),
                 };
+                if let Err(e) = mesh::validate_accessors(&document, &buffers) {
+                    return on_done(Err(e));
+                }
 
                 Self::load_image_data(
                     document,
                     base.clone().as_deref(),
                     buffers,
+                    context,
                     move |image_data, buffers, document| {
                         let images = match image_data {
                             Ok(data) => data,
@@ -132,9 +861,14 @@ impl GltfImporter {
                         };
 
                         on_done(Ok(ImportedGltfModel {
-                            images,
+                            images: images.images,
+                            lazy_images: images.lazy_images,
+                            compressed_images: images.compressed_images,
                             buffers,
                             document,
+                            warnings: Rc::new(images.warnings),
+                            extensions,
+                            unavailable_extensions: Rc::new(unavailable_extensions),
                         }))
                     },
                 );
@@ -142,293 +876,929 @@ impl GltfImporter {
         );
     }
 
-    fn load_buffer_data<F>(
-        document: Document,
-        base: Option<&Path>,
-        mut blob: Option<Vec<u8>>,
+    /// Loads and imports the `.gltf` or `.glb` file at `path`
+    ///
+    /// The file is loaded via the same mechanism as the resources it references (i.e. `three-d`'s Loader,
+    /// or the resolver set in the options), so `path` may also be an `http[s]://` URL on wasm.
+    /// Whether the file is a GLTF JSON or a binary GLB file is detected from its content.
+    /// The directory containing the file is used as `base` for relative references in the document.
+    ///
+    /// ```rust
+    /// use three_d_gltf_import::import::GltfImporter;
+    /// GltfImporter::import_from_path("./sample_models/2.0/ToyCar/glTF-Binary/ToyCar.glb", |imported| {
+    ///     let result = imported.unwrap();
+    ///     assert_eq!(result.images().len(), 8);
+    /// });
+    /// ```
+    pub fn import_from_path<P, F>(path: P, on_done: F) -> ImportHandle
+    where
+        P: AsRef<Path>,
+        F: 'static + FnOnce(Result<ImportedGltfModel>),
+    {
+        Self::import_from_path_with_options(path, ImportOptions::default(), on_done)
+    }
+
+    /// Loads and imports the `.gltf` or `.glb` file at `path` using the given `options`
+    ///
+    /// See [`import_from_path`](#method.import_from_path) for details
+    pub fn import_from_path_with_options<P, F>(
+        path: P,
+        options: ImportOptions,
         on_done: F,
-    ) where
-        F: 'static + FnOnce(Result<LoadedBuffers>, Document),
+    ) -> ImportHandle
+    where
+        P: AsRef<Path>,
+        F: 'static + FnOnce(Result<ImportedGltfModel>),
     {
-        let document_buffers = document.buffers();
-        let mut imported_buffers = Vec::with_capacity(document_buffers.len());
-        for buffer in document_buffers {
-            let imported_buffer = match buffer.source() {
-                buffer::Source::Uri(uri) => match Scheme::parse(uri) {
-                    Scheme::Data(_, base64) => BufferImport::Loaded {
+        let handle = ImportHandle::default();
+        let path = path.as_ref();
+        let base = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let resource = match path.file_name() {
+            Some(file_name) => Resource::Relative {
+                base: base.clone(),
+                uri: file_name.to_string_lossy().into_owned(),
+            },
+            None => {
+                on_done(Err(ImportError::Load {
+                    path: path.to_path_buf(),
+                    source: io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "path does not point to a file",
+                    ),
+                }));
+                return handle;
+            }
+        };
+
+        let resolver = options.resolver.clone();
+        let import_handle = handle.clone();
+        resolver.load(
+            vec![resource.clone()],
+            Box::new(move |mut loaded| {
+                if import_handle.is_cancelled() {
+                    return on_done(Err(ImportError::Cancelled));
+                }
+
+                let (document, blob, extensions) = match loaded.remove(&resource) {
+                    Some(Ok(data)) => match Extensions::parse(&data) {
+                        // The binary GLB chunk is passed on as part of the loaded file instead of a copy
+                        Ok((document, bin, extensions)) => {
+                            let data = Rc::new(data);
+                            let blob = bin.map(|bin| ResourceData::Slice(data, bin));
+                            (document, blob, extensions)
+                        }
+                        Err(e) => return on_done(Err(e.into())),
+                    },
+                    Some(Err(err)) => {
+                        return on_done(Err(ImportError::Load {
+                            path: resource.path(),
+                            source: err,
+                        }))
+                    }
+                    None => {
+                        return on_done(Err(ImportError::Load {
+                            path: resource.path(),
+                            source: io::Error::new(
+                                io::ErrorKind::NotFound,
+                                "resource has not been loaded by the resolver",
+                            ),
+                        }))
+                    }
+                };
+
+                Self::import_with_handle(
+                    document,
+                    blob,
+                    Some(extensions),
+                    Some(base),
+                    options,
+                    import_handle,
+                    on_done,
+                )
+            }),
+        );
+
+        handle
+    }
+
+    /// Imports a provided gltf document, returning a future that resolves with the imported document
+    ///
+    /// This is the `async` equivalent of [`import`](#method.import), see there for the meaning of `base`.
+    /// It works on desktop as well as on wasm, where it can be awaited e.g. via `wasm_bindgen_futures`.
+    /// Dropping the returned future cancels the import.
+    ///
+    /// Like [`import`](#method.import), this ignores the [extensions](struct.ImportedGltfModel.html#extensions)
+    /// `gltf` does not support. Use [`import_from_path_async`](#method.import_from_path_async) or
+    /// [`import_from_slice_async`](#method.import_from_slice_async) for documents using them.
+    ///
+    /// ```rust,no_run
+    /// use gltf::Gltf;
+    /// use std::path::PathBuf;
+    /// use three_d_gltf_import::error::Result;
+    /// use three_d_gltf_import::import::GltfImporter;
+    ///
+    /// async fn load_cube() -> Result<()> {
+    ///     let base = PathBuf::from("./sample_models/2.0/Cube/glTF");
+    ///     let gltf = Gltf::open(base.join("Cube.gltf"))?;
+    ///     let imported = GltfImporter::import_async(gltf, Some(base)).await?;
+    ///     assert_eq!(imported.images().len(), 2);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn import_async(gltf: Gltf, base: Option<PathBuf>) -> Result<ImportedGltfModel> {
+        Self::import_async_with_options(gltf, base, ImportOptions::default()).await
+    }
+
+    /// Imports a provided gltf document using the given `options`, returning a future that resolves with the imported document
+    ///
+    /// See [`import_async`](#method.import_async) for details
+    pub async fn import_async_with_options(
+        gltf: Gltf,
+        base: Option<PathBuf>,
+        options: ImportOptions,
+    ) -> Result<ImportedGltfModel> {
+        let (sender, receiver) = oneshot::channel();
+        let _cancel_on_drop = CancelOnDrop(Self::import_with_options(
+            gltf,
+            base,
+            options,
+            move |imported| {
+                // If the future has been dropped in the meantime, nobody is interested in the result anymore
+                let _ = sender.send(imported);
+            },
+        ));
+
+        // Cancelled imports still call `on_done`, so the sender is only dropped without a result if a resolver
+        // dropped its callback
+        receiver.await.unwrap_or(Err(ImportError::Aborted))
+    }
+
+    /// Parses and imports the `.gltf` or `.glb` file `data`, returning a future that resolves with the imported document
+    ///
+    /// This is the `async` equivalent of [`import_from_slice`](#method.import_from_slice), reading extensions of the
+    /// document not supported by `gltf`. See [`import_async`](#method.import_async) for details.
+    pub async fn import_from_slice_async(
+        data: &[u8],
+        base: Option<PathBuf>,
+    ) -> Result<ImportedGltfModel> {
+        Self::import_from_slice_async_with_options(data, base, ImportOptions::default()).await
+    }
+
+    /// Parses and imports the `.gltf` or `.glb` file `data` using the given `options`, returning a future that
+    /// resolves with the imported document
+    ///
+    /// See [`import_from_slice_async`](#method.import_from_slice_async) for details
+    pub async fn import_from_slice_async_with_options(
+        data: &[u8],
+        base: Option<PathBuf>,
+        options: ImportOptions,
+    ) -> Result<ImportedGltfModel> {
+        let (sender, receiver) = oneshot::channel();
+        let _cancel_on_drop = CancelOnDrop(Self::import_from_slice_with_options(
+            data,
+            base,
+            options,
+            move |imported| {
+                let _ = sender.send(imported);
+            },
+        ));
+
+        receiver.await.unwrap_or(Err(ImportError::Aborted))
+    }
+
+    /// Loads and imports the `.gltf` or `.glb` file at `path`, returning a future that resolves with the imported
+    /// document
+    ///
+    /// This is the `async` equivalent of [`import_from_path`](#method.import_from_path), reading extensions of the
+    /// document not supported by `gltf`. See [`import_async`](#method.import_async) for details.
+    pub async fn import_from_path_async<P>(path: P) -> Result<ImportedGltfModel>
+    where
+        P: AsRef<Path>,
+    {
+        Self::import_from_path_async_with_options(path, ImportOptions::default()).await
+    }
+
+    /// Loads and imports the `.gltf` or `.glb` file at `path` using the given `options`, returning a future that
+    /// resolves with the imported document
+    ///
+    /// See [`import_from_path_async`](#method.import_from_path_async) for details
+    pub async fn import_from_path_async_with_options<P>(
+        path: P,
+        options: ImportOptions,
+    ) -> Result<ImportedGltfModel>
+    where
+        P: AsRef<Path>,
+    {
+        let (sender, receiver) = oneshot::channel();
+        let _cancel_on_drop = CancelOnDrop(Self::import_from_path_with_options(
+            path,
+            options,
+            move |imported| {
+                let _ = sender.send(imported);
+            },
+        ));
+
+        receiver.await.unwrap_or(Err(ImportError::Aborted))
+    }
+
+    fn load_buffer_data<F>(
+        document: Document,
+        base: Option<&Path>,
+        mut blob: Option<ResourceData>,
+        context: Rc<ImportContext>,
+        on_done: F,
+    ) where
+        F: 'static + FnOnce(Result<LoadedBuffers>, Document),
+    {
+        let document_buffers = document.buffers();
+        let mut imported_buffers = Vec::with_capacity(document_buffers.len());
+        for buffer in document_buffers {
+            let error = |kind| ResourceError::new(ImportPhase::Buffers, buffer.index(), kind);
+            let imported_buffer = match buffer.source() {
+                buffer::Source::Uri(uri) => match Scheme::parse(uri) {
+                    Scheme::Data { base64, data, .. } => BufferImport::Loaded {
                         index: buffer.index(),
-                        data: match Self::load_buffer_from_data_uri(base64) {
-                            Ok(data) => data,
-                            Err(e) => return on_done(Err(e), document),
+                        data: match Self::load_buffer_from_data_uri(data, base64) {
+                            Ok(data) => data.into(),
+                            Err(kind) => {
+                                return on_done(Err(error(kind).with_uri(uri).into()), document)
+                            }
                         },
                         length: buffer.length(),
                     },
                     #[cfg(not(target_arch = "wasm32"))]
                     Scheme::File(path) => BufferImport::NeedsLoading {
                         index: buffer.index(),
-                        path: PathBuf::from(path),
+                        uri: uri.to_owned(),
+                        resource: Resource::File(PathBuf::from(path)),
+                        length: buffer.length(),
+                    },
+                    Scheme::Relative(path) if base.is_some() => BufferImport::NeedsLoading {
+                        index: buffer.index(),
+                        uri: uri.to_owned(),
+                        resource: Resource::Relative {
+                            base: base.unwrap().to_path_buf(),
+                            uri: path,
+                        },
                         length: buffer.length(),
                     },
-                    Scheme::Relative if base.is_some() => {
-                        let url = base.unwrap().join(uri);
-                        BufferImport::NeedsLoading {
-                            index: buffer.index(),
-                            path: url,
-                            length: buffer.length(),
-                        }
-                    }
                     Scheme::External(url) => BufferImport::NeedsLoading {
                         index: buffer.index(),
-                        path: PathBuf::from(url),
+                        uri: uri.to_owned(),
+                        resource: Resource::External(url.to_owned()),
                         length: buffer.length(),
                     },
-                    Scheme::Unsupported => return on_done(Err(Error::UnsupportedScheme), document),
-                    _ => return on_done(Err(Error::UnsupportedScheme), document),
+                    Scheme::Relative(_) => {
+                        let error = error(ResourceErrorKind::MissingBase).with_uri(uri);
+                        return on_done(Err(error.into()), document);
+                    }
+                    _ => {
+                        let error = error(ResourceErrorKind::UnsupportedScheme).with_uri(uri);
+                        return on_done(Err(error.into()), document);
+                    }
                 },
                 buffer::Source::Bin => BufferImport::Loaded {
                     index: buffer.index(),
                     data: match blob.take() {
                         Some(data) => data,
-                        None => return on_done(Err(Error::MissingBlob), document),
+                        None => {
+                            let error = error(ResourceErrorKind::MissingBlob);
+                            return on_done(Err(error.into()), document);
+                        }
                     },
                     length: buffer.length(),
                 },
             };
 
+            if let BufferImport::NeedsLoading { uri, resource, .. } = &imported_buffer {
+                if !context.options.sandbox.allows(resource) {
+                    let error = error(ResourceErrorKind::Forbidden)
+                        .with_uri(uri)
+                        .with_path(resource.path());
+                    return on_done(Err(error.into()), document);
+                }
+            }
+
             imported_buffers.push(imported_buffer);
         }
 
-        let paths: Vec<_> = imported_buffers
+        let cache = context.options.cache.as_ref();
+        let resources: Vec<_> = imported_buffers
             .iter()
-            .filter_map(|buffer| {
-                if let BufferImport::NeedsLoading { path, .. } = buffer {
-                    Some(path.clone())
-                } else {
-                    None
+            .filter_map(|buffer| match buffer {
+                BufferImport::NeedsLoading { resource, .. }
+                    if cache.and_then(|cache| cache.buffer(resource)).is_none() =>
+                {
+                    Some(resource.clone())
                 }
+                _ => None,
             })
             .collect();
 
-        Loader::load(paths.as_slice(), move |loaded| {
-            let result: Result<LoadedBuffers> = imported_buffers
-                .into_iter()
-                .map(|buffer| match buffer {
-                    BufferImport::NeedsLoading {
-                        index,
-                        path,
-                        length,
-                    } => match loaded.bytes(path) {
-                        Ok(bytes) => Ok((index, bytes.to_owned(), length)),
-                        #[cfg(not(target_arch = "wasm32"))]
-                        Err(IOError::IO(err)) => Err(Error::Io(err)),
-                        _ => Err(Error::MissingBlob),
-                    },
-                    BufferImport::Loaded {
-                        index,
-                        data,
-                        length,
-                    } => Ok((index, data, length)),
-                })
-                .map(|data| {
-                    let (index, mut buffer_data, length) = data?;
-                    if buffer_data.len() < length {
-                        return Err(Error::BufferLength {
-                            buffer: index,
-                            expected: length,
-                            actual: buffer_data.len(),
+        let mut load_options =
+            LoadOptions::new().with_progress(context.progress.loading(resources.len()));
+        if let Some(max) = context.options.limits.max_buffer_bytes() {
+            // Embedded buffers count against the limit as well
+            let embedded_bytes = imported_buffers
+                .iter()
+                .fold(0usize, |sum, buffer| match buffer {
+                    BufferImport::Loaded { data, .. } => sum.saturating_add(data.len()),
+                    BufferImport::NeedsLoading { .. } => sum,
+                });
+            load_options = load_options.with_max_bytes(max.saturating_sub(embedded_bytes));
+        }
+        let options = context.options.clone();
+        options.load_resources(
+            resources,
+            load_options,
+            Box::new(move |mut loaded| {
+                if context.handle.is_cancelled() {
+                    return on_done(Err(ImportError::Cancelled), document);
+                }
+                context.progress.loading_done();
+
+                let cache = context.options.cache.as_ref();
+                // Buffers referencing the same resource share its data
+                let mut shared = HashMap::new();
+                let mut total_bytes = 0;
+                let result: Result<LoadedBuffers> = imported_buffers
+                    .into_iter()
+                    .map(|buffer| match buffer {
+                        BufferImport::NeedsLoading {
+                            index,
+                            uri,
+                            resource,
+                            length,
+                        } => {
+                            let error = |kind| {
+                                ImportError::from(
+                                    ResourceError::new(ImportPhase::Buffers, index, kind)
+                                        .with_uri(&uri)
+                                        .with_path(resource.path()),
+                                )
+                            };
+                            let buffer = match cache
+                                .and_then(|cache| cache.buffer(&resource))
+                                .or_else(|| shared.get(&resource).cloned())
+                            {
+                                Some(buffer) => buffer,
+                                None => {
+                                    let buffer =
+                                        match loaded.remove(&resource) {
+                                            Some(Ok(data)) => Rc::new(data),
+                                            Some(Err(err)) => {
+                                                return Err(error(ResourceErrorKind::Load(err)))
+                                            }
+                                            None => return Err(error(ResourceErrorKind::Load(
+                                                io::Error::new(
+                                                    io::ErrorKind::NotFound,
+                                                    "resource has not been loaded by the resolver",
+                                                ),
+                                            ))),
+                                        };
+                                    if let Some(cache) = cache {
+                                        cache.insert_buffer(&resource, buffer.clone());
+                                    }
+                                    shared.insert(resource.clone(), buffer.clone());
+                                    buffer
+                                }
+                            };
+                            Self::check_buffer_length(buffer.len(), length)
+                                .map(|()| (index, buffer))
+                                .map_err(error)
+                        }
+                        BufferImport::Loaded {
+                            index,
+                            data,
+                            length,
+                        } => Self::check_buffer_length(data.len(), length)
+                            .map(|()| (index, Rc::new(data)))
+                            .map_err(|kind| {
+                                ResourceError::new(ImportPhase::Buffers, index, kind).into()
+                            }),
+                    })
+                    .map(|data| {
+                        let (index, buffer) = data?;
+                        total_bytes += buffer.len();
+                        if let Err(exceeded) =
+                            context.options.limits.check_buffer_bytes(total_bytes)
+                        {
+                            let kind = ResourceErrorKind::LimitExceeded(exceeded);
+                            return Err(
+                                ResourceError::new(ImportPhase::Buffers, index, kind).into()
+                            );
+                        }
+                        context.progress.finished(FinishedResource::Buffer {
+                            index,
+                            bytes: buffer.len(),
                         });
-                    }
-                    while buffer_data.len() % 4 != 0 {
-                        buffer_data.push(0);
-                    }
 
-                    Ok((index, buffer::Data(buffer_data)))
-                })
-                .collect();
+                        Ok((index, buffer))
+                    })
+                    .collect();
 
-            on_done(result, document);
-        });
+                on_done(result, document);
+            }),
+        );
+    }
+
+    fn check_buffer_length(
+        actual: usize,
+        length: usize,
+    ) -> std::result::Result<(), ResourceErrorKind> {
+        if actual < length {
+            return Err(ResourceErrorKind::BufferLength {
+                expected: length,
+                actual,
+            });
+        }
+
+        Ok(())
     }
 
-    fn load_buffer_from_data_uri(base64: &str) -> Result<Vec<u8>> {
-        base64::decode(&base64).map_err(Error::Base64)
+    fn load_buffer_from_data_uri(
+        data: &str,
+        base64: bool,
+    ) -> std::result::Result<Vec<u8>, ResourceErrorKind> {
+        if base64 {
+            base64::decode(data).map_err(ResourceErrorKind::Base64)
+        } else {
+            Ok(percent_decode(data))
+        }
     }
 
     fn load_image_data<F>(
         document: Document,
         base: Option<&Path>,
         buffer_data: LoadedBuffers,
+        context: Rc<ImportContext>,
         on_done: F,
     ) where
-        F: 'static + FnOnce(Result<LoadedImages>, LoadedBuffers, Document),
+        F: 'static + FnOnce(Result<ImportedImages>, LoadedBuffers, Document),
     {
         let document_images = document.images();
         let mut imported_images = Vec::with_capacity(document_images.len());
         for image in document_images {
-            let imported_image = match image.source() {
-                gltf_image::Source::Uri { uri, mime_type } if base.is_some() => {
-                    match Scheme::parse(uri) {
-                        Scheme::Data(media_type, base64) => ImageImport::Loaded {
-                            index: image.index(),
-                            data: match Self::load_image_from_data_uri(
-                                media_type.or(mime_type),
-                                base64,
-                            ) {
-                                Ok(data) => data,
-                                Err(e) => return on_done(Err(e), buffer_data, document),
-                            },
-                        },
-                        #[cfg(not(target_arch = "wasm32"))]
-                        Scheme::File(path) => ImageImport::NeedsLoading {
-                            index: image.index(),
-                            path: PathBuf::from(path),
-                            mime_type: mime_type.map(|mime| mime.to_owned()),
-                        },
-                        Scheme::Relative if base.is_some() => {
-                            let url = base.unwrap().join(uri);
-                            ImageImport::NeedsLoading {
-                                index: image.index(),
-                                path: url,
-                                mime_type: mime_type.map(|mime| mime.to_owned()),
-                            }
-                        }
-                        Scheme::External(url) => ImageImport::NeedsLoading {
-                            index: image.index(),
-                            path: PathBuf::from(url),
-                            mime_type: mime_type.map(|mime| mime.to_owned()),
-                        },
-                        Scheme::Unsupported => {
-                            return on_done(Err(Error::UnsupportedScheme), buffer_data, document)
-                        }
-                        _ => return on_done(Err(Error::UnsupportedScheme), buffer_data, document),
-                    }
-                }
-                gltf_image::Source::View { view, mime_type } => {
-                    let parent_buffer_data = match buffer_data.get(&view.buffer().index()) {
-                        Some(data) => data,
-                        None => return on_done(Err(Error::MissingBlob), buffer_data, document),
-                    };
-                    let begin = view.offset();
-                    let end = begin + view.length();
-                    let encoded_image = &parent_buffer_data[begin..end];
+            // WebP images are only allowed by `EXT_texture_webp`, without WebP support the textures use their
+            // fallback images instead
+            if !cfg!(feature = "webp") && Self::is_webp(&image, &context.extensions) {
+                context.progress.finished(FinishedResource::Image {
+                    index: image.index(),
+                    bytes: 0,
+                });
+                continue;
+            }
 
-                    let image_data = Self::load_image_from_buffer(encoded_image, Some(mime_type));
+            let error = |kind| ResourceError::new(ImportPhase::Images, image.index(), kind);
 
-                    match image_data {
-                        Ok(data) => ImageImport::Loaded {
+            let imported_image = match image.source() {
+                gltf_image::Source::Uri { uri, mime_type } => match Scheme::parse(uri) {
+                    Scheme::Data {
+                        media_type,
+                        base64,
+                        data,
+                    } => match Self::load_buffer_from_data_uri(data, base64) {
+                        Ok(data) => ImageImport::Embedded {
                             index: image.index(),
+                            uri: uri.to_owned(),
                             data,
+                            mime_type: media_type.or(mime_type).map(|mime| mime.to_owned()),
+                        },
+                        Err(kind) => ImageImport::Failed(error(kind).with_uri(uri)),
+                    },
+                    #[cfg(not(target_arch = "wasm32"))]
+                    Scheme::File(path) => ImageImport::NeedsLoading {
+                        index: image.index(),
+                        uri: uri.to_owned(),
+                        resource: Resource::File(PathBuf::from(path)),
+                        mime_type: mime_type.map(|mime| mime.to_owned()),
+                    },
+                    Scheme::Relative(path) if base.is_some() => ImageImport::NeedsLoading {
+                        index: image.index(),
+                        uri: uri.to_owned(),
+                        resource: Resource::Relative {
+                            base: base.unwrap().to_path_buf(),
+                            uri: path,
                         },
-                        Err(err) => return on_done(Err(err), buffer_data, document),
+                        mime_type: mime_type.map(|mime| mime.to_owned()),
+                    },
+                    Scheme::External(url) => ImageImport::NeedsLoading {
+                        index: image.index(),
+                        uri: uri.to_owned(),
+                        resource: Resource::External(url.to_owned()),
+                        mime_type: mime_type.map(|mime| mime.to_owned()),
+                    },
+                    Scheme::Relative(_) => {
+                        ImageImport::Failed(error(ResourceErrorKind::MissingBase).with_uri(uri))
                     }
-                }
-                _ => {
-                    return on_done(
-                        Err(Error::ExternalReferenceInSliceImport),
-                        buffer_data,
-                        document,
+                    _ => ImageImport::Failed(
+                        error(ResourceErrorKind::UnsupportedScheme).with_uri(uri),
+                    ),
+                },
+                gltf_image::Source::View { view, mime_type } => ImageImport::View {
+                    index: image.index(),
+                    buffer: view.buffer().index(),
+                    begin: view.offset(),
+                    end: view.offset() + view.length(),
+                    mime_type: mime_type.to_owned(),
+                },
+            };
+
+            let imported_image = match imported_image {
+                ImageImport::NeedsLoading { uri, resource, .. }
+                    if !context.options.sandbox.allows(&resource) =>
+                {
+                    let path = resource.path();
+                    ImageImport::Failed(
+                        error(ResourceErrorKind::Forbidden)
+                            .with_uri(&uri)
+                            .with_path(path),
                     )
                 }
+                imported_image => imported_image,
             };
 
             imported_images.push(imported_image);
         }
 
-        let paths: Vec<_> = imported_images
+        // Images that are known to be broken without any loading fail a strict import right away
+        if context.options.image_failures == ImageFailurePolicy::Fail {
+            let failed = imported_images
+                .iter()
+                .position(|image| matches!(image, ImageImport::Failed(_)));
+            if let Some(ImageImport::Failed(error)) =
+                failed.map(|position| imported_images.swap_remove(position))
+            {
+                return on_done(Err(error.into()), buffer_data, document);
+            }
+        }
+
+        let lazy = context.options.lazy_images;
+        let cache = context.options.cache.as_ref();
+        let resources: Vec<_> = imported_images
             .iter()
-            .filter_map(|buffer| {
-                if let ImageImport::NeedsLoading { path, .. } = buffer {
-                    Some(path.clone())
-                } else {
-                    None
+            .filter_map(|image| match image {
+                ImageImport::NeedsLoading { resource, .. }
+                    if cache
+                        .and_then(|cache| cache.image(resource, lazy))
+                        .is_none() =>
+                {
+                    Some(resource.clone())
                 }
+                _ => None,
             })
             .collect();
 
-        Loader::load(paths.as_slice(), move |loaded| {
-            let result: Result<LoadedImages> = imported_images
-                .into_iter()
-                .map(|image| match image {
-                    ImageImport::NeedsLoading {
-                        index,
-                        path,
-                        mime_type,
-                    } => match loaded.bytes(path) {
-                        Ok(bytes) => {
-                            let image_data =
-                                Self::load_image_from_buffer(bytes, mime_type.as_deref())?;
-
-                            Ok((index, image_data))
+        let load_options =
+            LoadOptions::new().with_progress(context.progress.loading(resources.len()));
+        let options = context.options.clone();
+        options.load_resources(
+            resources,
+            load_options,
+            Box::new(move |loaded| {
+                if context.handle.is_cancelled() {
+                    return on_done(Err(ImportError::Cancelled), buffer_data, document);
+                }
+                context.progress.loading_done();
+
+                // Errors are taken out of the loaded resources, so that they are passed on with their source
+                let mut load_errors = LoadErrors::new(&imported_images);
+                let loaded: HashMap<_, _> = loaded
+                    .into_iter()
+                    .filter_map(|(resource, data)| match data {
+                        Ok(data) => Some((resource, data)),
+                        Err(err) => {
+                            load_errors.insert(resource, err);
+                            None
                         }
-                        #[cfg(not(target_arch = "wasm32"))]
-                        Err(IOError::IO(err)) => Err(Error::Io(err)),
-                        _ => Err(Error::MissingBlob),
-                    },
-                    ImageImport::Loaded { index, data } => Ok((index, data)),
-                })
-                .collect();
+                    })
+                    .collect();
 
-            on_done(result, buffer_data, document);
-        });
+                // Gather the encoded data and check it against the limits before anything gets decoded
+                let lazy = context.options.lazy_images;
+                let cache = context.options.cache.as_ref();
+                let mut encoded_images = Vec::with_capacity(imported_images.len());
+                let mut cached_images = Vec::new();
+                for image in imported_images {
+                    let encoded_image = match image {
+                        ImageImport::NeedsLoading {
+                            index,
+                            uri,
+                            resource,
+                            mime_type,
+                        } => {
+                            if let Some(cached) =
+                                cache.and_then(|cache| cache.image(&resource, lazy))
+                            {
+                                let source = ImageSource {
+                                    index,
+                                    uri: Some(uri),
+                                    resource: Some(resource),
+                                };
+                                cached_images.push(source.cached(cached, &context));
+                                continue;
+                            }
+
+                            let data = loaded.get(&resource);
+                            let error = match data {
+                                Some(_) => None,
+                                None => load_errors.take(&resource),
+                            };
+                            let source = ImageSource {
+                                index,
+                                uri: Some(uri),
+                                resource: Some(resource),
+                            };
+                            match (data, error) {
+                                (Some(bytes), _) => source.encoded(
+                                    Cow::Borrowed(bytes),
+                                    mime_type.as_deref(),
+                                    &context,
+                                ),
+                                (None, Some(err)) => {
+                                    Err(source.error(ResourceErrorKind::Load(err)))
+                                }
+                                (None, None) => {
+                                    Err(source.error(ResourceErrorKind::Load(io::Error::new(
+                                        io::ErrorKind::NotFound,
+                                        "resource has not been loaded by the resolver",
+                                    ))))
+                                }
+                            }
+                        }
+                        ImageImport::Embedded {
+                            index,
+                            uri,
+                            data,
+                            mime_type,
+                        } => ImageSource {
+                            index,
+                            uri: Some(uri),
+                            resource: None,
+                        }
+                        .encoded(
+                            Cow::Owned(data),
+                            mime_type.as_deref(),
+                            &context,
+                        ),
+                        ImageImport::View {
+                            index,
+                            buffer,
+                            begin,
+                            end,
+                            mime_type,
+                        } => {
+                            let source = ImageSource {
+                                index,
+                                uri: None,
+                                resource: None,
+                            };
+                            match buffer_data.get(&buffer) {
+                                Some(parent_buffer_data) => {
+                                    match parent_buffer_data.get(begin..end) {
+                                        Some(data) => source.encoded(
+                                            Cow::Borrowed(data),
+                                            Some(&mime_type),
+                                            &context,
+                                        ),
+                                        None => {
+                                            Err(source.error(ResourceErrorKind::BufferLength {
+                                                expected: end,
+                                                actual: parent_buffer_data.len(),
+                                            }))
+                                        }
+                                    }
+                                }
+                                None => Err(source.error(ResourceErrorKind::MissingBuffer(buffer))),
+                            }
+                        }
+                        ImageImport::Failed(error) => Err(Box::new(error)),
+                    };
+                    encoded_images.push(encoded_image);
+                }
+
+                let mut images = LoadedImages::new();
+                let mut lazy_images = LazyImages::new();
+                let mut compressed_images = CompressedImages::new();
+                let mut warnings = Vec::new();
+                let mut failure = None;
+
+                // Images are reported (and fail a strict import) one by one, as soon as they are available
+                let mut add_image = |image: ImageResult<(usize, SharedImage, usize)>| {
+                    if failure.is_some() {
+                        return false;
+                    }
+                    match image {
+                        Ok((index, data, length)) => {
+                            context.progress.finished(FinishedResource::Image {
+                                index,
+                                bytes: length,
+                            });
+                            match data {
+                                SharedImage::Decoded(data) => {
+                                    images.insert(index, data);
+                                }
+                                SharedImage::Lazy(data) => {
+                                    lazy_images.insert(index, data);
+                                }
+                                SharedImage::Compressed(data) => {
+                                    compressed_images.insert(index, data);
+                                }
+                            }
+                        }
+                        Err(error) => {
+                            let index = error.index;
+                            match context.options.image_failures {
+                                ImageFailurePolicy::Fail => {
+                                    failure = Some(error);
+                                    return false;
+                                }
+                                ImageFailurePolicy::Placeholder => {
+                                    images.insert(index, Rc::new(placeholder_image()));
+                                }
+                                ImageFailurePolicy::Omit => {}
+                            }
+                            context
+                                .progress
+                                .finished(FinishedResource::Image { index, bytes: 0 });
+                            warnings.push(*error);
+                        }
+                    }
+                    true
+                };
+                let mut add_decoded_image = |image: DecodedImage| {
+                    add_image(image.map(|(source, image, length)| {
+                        let image = image.into_shared();
+                        if let (Some(cache), Some(resource)) = (cache, source.resource) {
+                            cache.insert_image(&resource, image.clone(), length);
+                        }
+                        (source.index, image, length)
+                    }))
+                };
+
+                if lazy {
+                    for image in encoded_images {
+                        if !add_decoded_image(image.map(EncodedImage::into_lazy)) {
+                            break;
+                        }
+                    }
+                } else {
+                    decode_images(encoded_images, &mut add_decoded_image);
+                }
+                for image in cached_images {
+                    if !add_image(image) {
+                        break;
+                    }
+                }
+
+                if let Some(error) = failure {
+                    return on_done(Err(ImportError::Resource(error)), buffer_data, document);
+                }
+
+                let images = ImportedImages {
+                    images,
+                    lazy_images,
+                    compressed_images,
+                    warnings,
+                };
+                on_done(Ok(images), buffer_data, document);
+            }),
+        );
     }
 
     fn guess_format(encoded_image: &[u8]) -> Option<ImageFormat> {
         match image::guess_format(encoded_image) {
             Ok(Png) => Some(Png),
             Ok(Jpeg) => Some(Jpeg),
+            #[cfg(feature = "webp")]
+            Ok(ImageFormat::WebP) => Some(ImageFormat::WebP),
             _ => None,
         }
     }
 
+    /// Whether the image is WebP encoded, judging by its MIME type, file extension or `EXT_texture_webp`
+    fn is_webp(image: &gltf::Image, extensions: &Extensions) -> bool {
+        extensions.is_webp_image(image.index())
+            || match image.source() {
+                gltf_image::Source::View { mime_type, .. } => mime_type == "image/webp",
+                gltf_image::Source::Uri { uri, mime_type } => {
+                    mime_type == Some("image/webp")
+                        || match Scheme::parse(uri) {
+                            Scheme::Data { media_type, .. } => media_type == Some("image/webp"),
+                            _ => without_query(uri).to_ascii_lowercase().ends_with(".webp"),
+                        }
+                }
+            }
+    }
+
     fn mime_type_to_image_format(
         encoded_image: &[u8],
         mime_type: Option<&str>,
-    ) -> Result<ImageFormat> {
+    ) -> std::result::Result<ImageFormat, ResourceErrorKind> {
         match mime_type {
             Some(t) => match t.as_ref() {
                 "image/png" => Ok(Png),
                 "image/jpeg" => Ok(Jpeg),
+                #[cfg(feature = "webp")]
+                "image/webp" => Ok(ImageFormat::WebP),
                 _ => match Self::guess_format(&encoded_image) {
                     Some(format) => Ok(format),
-                    None => Err(Error::UnsupportedImageEncoding),
+                    None => Err(ResourceErrorKind::UnsupportedImageEncoding),
                 },
             },
             None => match Self::guess_format(&encoded_image) {
                 Some(format) => Ok(format),
-                None => Err(Error::UnsupportedImageEncoding),
+                None => Err(ResourceErrorKind::UnsupportedImageEncoding),
             },
         }
     }
 
-    fn load_image_from_data_uri(mime_type: Option<&str>, base64: &str) -> Result<DynamicImage> {
-        let encoded_image = base64::decode(&base64).map_err(Error::Base64)?;
-        let encoded_format = Self::mime_type_to_image_format(&encoded_image, mime_type)?;
-        let decoded_image = image::load_from_memory_with_format(&encoded_image, encoded_format)?;
-        Ok(decoded_image)
+    /// Detects the format of the encoded image and checks it against the limits
+    fn encoded_format(
+        buffer: &[u8],
+        mime_type: Option<&str>,
+        context: &ImportContext,
+    ) -> std::result::Result<EncodedFormat, ResourceErrorKind> {
+        if mime_type == Some("image/ktx2") || ktx2::is_ktx2(buffer) {
+            let header = Ktx2Header::parse(buffer).ok_or_else(|| {
+                ResourceErrorKind::Decode(image::ImageError::Decoding(
+                    image::error::DecodingError::new(
+                        image::error::ImageFormatHint::Name("KTX2".to_owned()),
+                        "invalid KTX2 data",
+                    ),
+                ))
+            })?;
+            // 1D images have a height of 0
+            Self::check_image_limits(header.width, header.height.max(1), context)?;
+            return Ok(EncodedFormat::Ktx2(header));
+        }
+
+        let format = Self::mime_type_to_image_format(buffer, mime_type)?;
+        Self::check_encoded_image(buffer, format, context)?;
+        Ok(EncodedFormat::Image(format))
+    }
+
+    /// Checks the dimensions of the encoded image against the limits
+    fn check_encoded_image(
+        buffer: &[u8],
+        format: ImageFormat,
+        context: &ImportContext,
+    ) -> std::result::Result<(), ResourceErrorKind> {
+        // Check the dimensions before decoding, so huge images don't get allocated at all
+        if context.options.limits.limits_images() {
+            let (width, height) = image::io::Reader::with_format(io::Cursor::new(buffer), format)
+                .into_dimensions()
+                .map_err(ResourceErrorKind::Decode)?;
+            Self::check_image_limits(width, height, context)?;
+        }
+
+        Ok(())
     }
 
-    fn load_image_from_buffer(buffer: &[u8], mime_type: Option<&str>) -> Result<DynamicImage> {
-        let encoded_format = Self::mime_type_to_image_format(buffer, mime_type)?;
-        let decoded_image = image::load_from_memory_with_format(buffer, encoded_format)?;
+    /// Checks the dimensions of an image against the limits, adding its memory to the images of the import
+    fn check_image_limits(
+        width: u32,
+        height: u32,
+        context: &ImportContext,
+    ) -> std::result::Result<(), ResourceErrorKind> {
+        let limits = &context.options.limits;
+        limits
+            .check_image_dimensions(width, height)
+            .map_err(ResourceErrorKind::LimitExceeded)?;
 
-        Ok(decoded_image)
+        let image_bytes = (width as usize)
+            .saturating_mul(height as usize)
+            .saturating_mul(4)
+            .saturating_add(context.image_bytes.get());
+        limits
+            .check_image_bytes(image_bytes)
+            .map_err(ResourceErrorKind::LimitExceeded)?;
+        context.image_bytes.set(image_bytes);
+
+        Ok(())
     }
 }
 
 /// Represents the set of URI schemes the importer supports.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 enum Scheme<'a> {
-    /// `data:[<media type>];base64,<data>`.
-    Data(Option<&'a str>, &'a str),
+    /// `data:[<media type>][;<parameter>]*[;base64],<data>`.
+    Data {
+        /// The media type without parameters, `None` if omitted.
+        media_type: Option<&'a str>,
+        /// Whether `data` is base64 encoded, it is percent-encoded otherwise.
+        base64: bool,
+        data: &'a str,
+    },
 
-    /// `file:[//]<absolute file path>`.
+    /// `file:[//[localhost]]<absolute file path>`, with the path percent-decoded.
     ///
-    /// Note: The file scheme does not implement authority.
+    /// Note: The file scheme does not implement other authorities than `localhost`.
     #[cfg(not(target_arch = "wasm32"))]
-    File(&'a str),
+    File(String),
 
-    /// `../foo`, etc.
-    Relative,
+    /// `../foo`, etc., percent-decoded and without query or fragment.
+    Relative(String),
 
     // http[s]://<host>/<path>
     External(&'a str),
@@ -438,42 +1808,255 @@ enum Scheme<'a> {
 }
 
 impl<'a> Scheme<'a> {
-    fn parse(uri: &str) -> Scheme {
-        if uri.contains(":") {
-            if uri.starts_with("data:") {
-                let match0 = &uri["data:".len()..].split(";base64,").nth(0);
-                let match1 = &uri["data:".len()..].split(";base64,").nth(1);
-                if match1.is_some() {
-                    Scheme::Data(Some(match0.unwrap()), match1.unwrap())
-                } else if match0.is_some() {
-                    Scheme::Data(None, match0.unwrap())
-                } else {
-                    Scheme::Unsupported
+    fn parse(uri: &'a str) -> Scheme<'a> {
+        let (scheme, rest) = match split_scheme(uri) {
+            Some(split) => split,
+            None => return Scheme::Relative(percent_decode_path(without_query(uri))),
+        };
+
+        if scheme.eq_ignore_ascii_case("data") {
+            Self::parse_data(rest)
+        } else if scheme.eq_ignore_ascii_case("file") {
+            Self::parse_file(rest)
+        } else if scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https") {
+            Scheme::External(uri)
+        } else {
+            Scheme::Unsupported
+        }
+    }
+
+    fn parse_data(rest: &'a str) -> Scheme<'a> {
+        let separator = match rest.find(',') {
+            Some(separator) => separator,
+            None => return Scheme::Unsupported,
+        };
+        let mut parameters = rest[..separator].split(';');
+        let media_type = parameters
+            .next()
+            .map(str::trim)
+            .filter(|media_type| !media_type.is_empty());
+        let base64 = parameters.any(|parameter| parameter.trim().eq_ignore_ascii_case("base64"));
+
+        Scheme::Data {
+            media_type,
+            base64,
+            data: &rest[separator + 1..],
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn parse_file(rest: &'a str) -> Scheme<'a> {
+        let path = match rest.strip_prefix("//") {
+            Some(authority_and_path) => {
+                let (authority, path) = authority_and_path.split_at(
+                    authority_and_path
+                        .find('/')
+                        .unwrap_or(authority_and_path.len()),
+                );
+                if !authority.is_empty() && !authority.eq_ignore_ascii_case("localhost") {
+                    return Scheme::Unsupported;
                 }
-            } else if uri.starts_with("file://") {
-                #[cfg(not(target_arch = "wasm32"))]
-                return Scheme::File(&uri["file://".len()..]);
-                #[cfg(target_arch = "wasm32")]
-                return Scheme::Unsupported;
-            } else if uri.starts_with("file:") {
-                #[cfg(not(target_arch = "wasm32"))]
-                return Scheme::File(&uri["file:".len()..]);
-                #[cfg(target_arch = "wasm32")]
-                return Scheme::Unsupported;
-            } else if uri.starts_with("http:") || uri.starts_with("https:") {
-                Scheme::External(&uri)
-            } else {
-                Scheme::Unsupported
+                path
+            }
+            None => rest,
+        };
+
+        Scheme::File(percent_decode_path(without_query(path)))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn parse_file(_rest: &'a str) -> Scheme<'a> {
+        Scheme::Unsupported
+    }
+}
+
+/// Splits `uri` into its scheme and the remainder, `None` for relative references (RFC 3986, section 3.1)
+fn split_scheme(uri: &str) -> Option<(&str, &str)> {
+    let colon = uri.find(':')?;
+    let scheme = &uri[..colon];
+    let mut chars = scheme.chars();
+    let is_scheme = matches!(chars.next(), Some(c) if c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.');
+
+    if is_scheme {
+        Some((scheme, &uri[colon + 1..]))
+    } else {
+        None
+    }
+}
+
+/// Strips query and fragment from the path of a URI
+fn without_query(path: &str) -> &str {
+    match path.find(&['?', '#'][..]) {
+        Some(end) => &path[..end],
+        None => path,
+    }
+}
+
+/// Decodes `%XX` escapes, invalid escapes are kept as they are
+fn percent_decode(input: &str) -> Vec<u8> {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let escaped = bytes
+                .get(i + 1..i + 3)
+                .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+                .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+            if let Some(byte) = escaped {
+                decoded.push(byte);
+                i += 3;
+                continue;
             }
-        } else {
-            Scheme::Relative
         }
+        decoded.push(bytes[i]);
+        i += 1;
     }
+
+    decoded
+}
+
+fn percent_decode_path(path: &str) -> String {
+    String::from_utf8_lossy(&percent_decode(path)).into_owned()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::limits::Limit;
+    use crate::test_util::PNG_DATA_URI;
+    use futures_executor::block_on;
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_parse_uri() {
+        let cases = [
+            // Examples from the GLTF specification
+            ("duck.png", Scheme::Relative("duck.png".to_owned())),
+            ("buffer.bin", Scheme::Relative("buffer.bin".to_owned())),
+            (
+                "data:application/octet-stream;base64,AAAA",
+                Scheme::Data {
+                    media_type: Some("application/octet-stream"),
+                    base64: true,
+                    data: "AAAA",
+                },
+            ),
+            (
+                "data:application/gltf-buffer;base64,AAAA",
+                Scheme::Data {
+                    media_type: Some("application/gltf-buffer"),
+                    base64: true,
+                    data: "AAAA",
+                },
+            ),
+            // Relative references
+            (
+                "textures/my%20wood.png",
+                Scheme::Relative("textures/my wood.png".to_owned()),
+            ),
+            (
+                "../shared/%E6%9C%A8.png",
+                Scheme::Relative("../shared/\u{6728}.png".to_owned()),
+            ),
+            ("wood%zz.png", Scheme::Relative("wood%zz.png".to_owned())),
+            (
+                "wood.png?version=2#top",
+                Scheme::Relative("wood.png".to_owned()),
+            ),
+            (
+                "textures/a:b.png",
+                Scheme::Relative("textures/a:b.png".to_owned()),
+            ),
+            // Data URIs
+            (
+                "data:image/png;charset=utf-8;base64,AAAA",
+                Scheme::Data {
+                    media_type: Some("image/png"),
+                    base64: true,
+                    data: "AAAA",
+                },
+            ),
+            (
+                "DATA:;BASE64,AAAA",
+                Scheme::Data {
+                    media_type: None,
+                    base64: true,
+                    data: "AAAA",
+                },
+            ),
+            (
+                "data:,Hello%2C%20World",
+                Scheme::Data {
+                    media_type: None,
+                    base64: false,
+                    data: "Hello%2C%20World",
+                },
+            ),
+            (
+                "data:text/plain;charset=US-ASCII,abc",
+                Scheme::Data {
+                    media_type: Some("text/plain"),
+                    base64: false,
+                    data: "abc",
+                },
+            ),
+            ("data:image/png;base64", Scheme::Unsupported),
+            // External
+            (
+                "HTTP://example.com/wood.png",
+                Scheme::External("HTTP://example.com/wood.png"),
+            ),
+            (
+                "https://example.com/my%20wood.png",
+                Scheme::External("https://example.com/my%20wood.png"),
+            ),
+            ("ftp://example.com/wood.png", Scheme::Unsupported),
+            ("c:wood.png", Scheme::Unsupported),
+        ];
+
+        for (uri, expected) in cases.iter() {
+            assert_eq!(&Scheme::parse(uri), expected, "{}", uri);
+        }
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_parse_file_uri() {
+        let cases = [
+            (
+                "file:///models/my%20wood.png",
+                Scheme::File("/models/my wood.png".to_owned()),
+            ),
+            (
+                "file:/models/wood.png",
+                Scheme::File("/models/wood.png".to_owned()),
+            ),
+            (
+                "FILE://localhost/models/wood.png",
+                Scheme::File("/models/wood.png".to_owned()),
+            ),
+            ("file://server/models/wood.png", Scheme::Unsupported),
+        ];
+
+        for (uri, expected) in cases.iter() {
+            assert_eq!(&Scheme::parse(uri), expected, "{}", uri);
+        }
+    }
+
+    #[test]
+    fn test_load_buffer_from_data_uri() {
+        assert_eq!(
+            GltfImporter::load_buffer_from_data_uri("Hello%2C%20World", false).unwrap(),
+            b"Hello, World"
+        );
+        assert_eq!(
+            GltfImporter::load_buffer_from_data_uri("SGVsbG8=", true).unwrap(),
+            b"Hello"
+        );
+        assert!(GltfImporter::load_buffer_from_data_uri("%%%", true).is_err());
+    }
 
     #[test]
     fn test_import_triangle_model() {
@@ -487,7 +2070,7 @@ mod tests {
             let result = imported.unwrap();
             assert_eq!(result.buffers().len(), 1);
             assert_eq!(result.images().len(), 0);
-        })
+        });
     }
 
     #[test]
@@ -502,7 +2085,7 @@ mod tests {
             let result = imported.unwrap();
             assert_eq!(result.buffers().len(), 1);
             assert_eq!(result.images().len(), 0);
-        })
+        });
     }
 
     #[test]
@@ -517,7 +2100,7 @@ mod tests {
             let result = imported.unwrap();
             assert_eq!(result.buffers().len(), 1);
             assert_eq!(result.images().len(), 2);
-        })
+        });
     }
 
     #[test]
@@ -532,7 +2115,7 @@ mod tests {
             let result = imported.unwrap();
             assert_eq!(result.buffers().len(), 1);
             assert_eq!(result.images().len(), 0);
-        })
+        });
     }
 
     #[test]
@@ -547,7 +2130,7 @@ mod tests {
             let result = imported.unwrap();
             assert_eq!(result.buffers().len(), 1);
             assert_eq!(result.images().len(), 0);
-        })
+        });
     }
 
     #[test]
@@ -562,7 +2145,7 @@ mod tests {
             let result = imported.unwrap();
             assert_eq!(result.buffers().len(), 1);
             assert_eq!(result.images().len(), 1);
-        })
+        });
     }
 
     #[test]
@@ -577,7 +2160,71 @@ mod tests {
             let result = imported.unwrap();
             assert_eq!(result.buffers().len(), 1);
             assert_eq!(result.images().len(), 1);
-        })
+        });
+    }
+
+    #[test]
+    fn test_import_fox_model_with_embedded_data_without_base() {
+        let gltf = Gltf::open(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Fox/glTF-Embedded/Fox.gltf"
+        ))
+        .unwrap();
+        GltfImporter::import(gltf, None, |imported| {
+            let result = imported.unwrap();
+            assert_eq!(result.buffers().len(), 1);
+            assert_eq!(result.images().len(), 1);
+        });
+    }
+
+    #[test]
+    fn test_import_fox_model_binary_without_base() {
+        let gltf = Gltf::open(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Fox/glTF-Binary/Fox.glb"
+        ))
+        .unwrap();
+        GltfImporter::import(gltf, None, |imported| {
+            let result = imported.unwrap();
+            assert_eq!(result.buffers().len(), 1);
+            assert_eq!(result.images().len(), 1);
+        });
+    }
+
+    #[test]
+    fn test_import_relative_image_without_base() {
+        let gltf = Gltf::open(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Cube/glTF/Cube.gltf"
+        ))
+        .unwrap();
+        let mut json = gltf.document.into_json();
+        // Embed the (relative) buffer, so only the images need a base path
+        let buffer = std::fs::read(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Cube/glTF/Cube.bin"
+        ))
+        .unwrap();
+        json.buffers[0].uri = Some(format!(
+            "data:application/octet-stream;base64,{}",
+            base64::encode(&buffer)
+        ));
+        let gltf = Gltf {
+            document: Document::from_json(json).unwrap(),
+            blob: None,
+        };
+
+        GltfImporter::import(gltf, None, |imported| match imported.unwrap_err() {
+            ImportError::Resource(error) => {
+                assert_eq!(error.phase, ImportPhase::Images);
+                assert!(matches!(error.kind, ResourceErrorKind::MissingBase));
+            }
+            error => panic!("unexpected error: {}", error),
+        });
     }
 
     #[test]
@@ -592,7 +2239,7 @@ mod tests {
             let result = imported.unwrap();
             assert_eq!(result.buffers().len(), 1);
             assert_eq!(result.images().len(), 1);
-        })
+        });
     }
 
     #[test]
@@ -607,7 +2254,7 @@ mod tests {
             let result = imported.unwrap();
             assert_eq!(result.buffers().len(), 1);
             assert_eq!(result.images().len(), 8);
-        })
+        });
     }
 
     #[test]
@@ -622,6 +2269,634 @@ mod tests {
             let result = imported.unwrap();
             assert_eq!(result.buffers().len(), 1);
             assert_eq!(result.images().len(), 8);
-        })
+        });
+    }
+
+    #[test]
+    fn test_import_async_cube_model() {
+        let base = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Cube/glTF"
+        ));
+        let gltf = Gltf::open(base.join("Cube.gltf")).unwrap();
+        let result = block_on(GltfImporter::import_async(gltf, Some(base))).unwrap();
+        assert_eq!(result.buffers().len(), 1);
+        assert_eq!(result.images().len(), 2);
+    }
+
+    #[test]
+    fn test_import_from_path_cube_model() {
+        let path = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Cube/glTF/Cube.gltf"
+        ));
+        GltfImporter::import_from_path(path, |imported| {
+            let result = imported.unwrap();
+            assert_eq!(result.buffers().len(), 1);
+            assert_eq!(result.images().len(), 2);
+        });
+    }
+
+    #[test]
+    fn test_import_from_path_fox_model_binary() {
+        let path = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Fox/glTF-Binary/Fox.glb"
+        ));
+        GltfImporter::import_from_path(path, |imported| {
+            let result = imported.unwrap();
+            assert_eq!(result.buffers().len(), 1);
+            assert_eq!(result.images().len(), 1);
+        });
+    }
+
+    #[test]
+    fn test_import_from_path_async_cube_model() {
+        let path = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Cube/glTF/Cube.gltf"
+        ));
+        let result = block_on(GltfImporter::import_from_path_async(path)).unwrap();
+        assert_eq!(result.buffers().len(), 1);
+        assert_eq!(result.images().len(), 2);
+        assert!(result.unavailable_extensions().is_empty());
+    }
+
+    #[test]
+    fn test_import_from_missing_path() {
+        let path = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Missing/glTF/Missing.gltf"
+        ));
+        GltfImporter::import_from_path(&path, |imported| {
+            assert!(imported.is_err());
+        });
+        assert!(block_on(GltfImporter::import_from_path_async(path)).is_err());
+    }
+
+    #[test]
+    fn test_import_reports_missing_image() {
+        let base = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Cube/glTF"
+        ));
+        let mut gltf = Gltf::open(base.join("Cube.gltf")).unwrap();
+        let mut json = gltf.document.into_json();
+        json.images[1].uri = Some("Missing.png".to_owned());
+        gltf.document = Document::from_json(json).unwrap();
+
+        GltfImporter::import(gltf, Some(base.clone()), move |imported| {
+            match imported.unwrap_err() {
+                ImportError::Resource(error) => {
+                    assert_eq!(error.phase, ImportPhase::Images);
+                    assert_eq!(error.index, 1);
+                    assert_eq!(error.uri.as_deref(), Some("Missing.png"));
+                    assert_eq!(error.path, Some(base.join("Missing.png")));
+                    assert!(matches!(error.kind, ResourceErrorKind::Load(_)));
+                }
+                error => panic!("unexpected error: {}", error),
+            }
+        });
+    }
+
+    #[test]
+    fn test_import_with_placeholder_for_missing_image() {
+        let base = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Cube/glTF"
+        ));
+        let mut gltf = Gltf::open(base.join("Cube.gltf")).unwrap();
+        let mut json = gltf.document.into_json();
+        json.images[1].uri = Some("Missing.png".to_owned());
+        gltf.document = Document::from_json(json).unwrap();
+
+        let options = ImportOptions::new().with_image_failures(ImageFailurePolicy::Placeholder);
+        GltfImporter::import_with_options(gltf, Some(base), options, |imported| {
+            let result = imported.unwrap();
+            assert_eq!(result.buffers().len(), 1);
+            assert_eq!(result.images().len(), 2);
+            assert_eq!(result.images()[&1].to_rgba8().dimensions(), (1, 1));
+            assert_eq!(result.warnings().len(), 1);
+            assert_eq!(result.warnings()[0].index, 1);
+            assert!(!result.cpu_meshes().is_empty());
+        });
+    }
+
+    #[test]
+    fn test_import_omitting_missing_image() {
+        let base = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Cube/glTF"
+        ));
+        let mut gltf = Gltf::open(base.join("Cube.gltf")).unwrap();
+        let mut json = gltf.document.into_json();
+        json.images[1].uri = Some("Missing.png".to_owned());
+        gltf.document = Document::from_json(json).unwrap();
+
+        let options = ImportOptions::new().with_image_failures(ImageFailurePolicy::Omit);
+        GltfImporter::import_with_options(gltf, Some(base), options, |imported| {
+            let result = imported.unwrap();
+            assert_eq!(result.images().len(), 1);
+            assert!(result.images().contains_key(&0));
+            assert_eq!(result.warnings().len(), 1);
+        });
+    }
+
+    #[test]
+    fn test_import_rejects_path_outside_of_sandbox() {
+        let base = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Cube/glTF"
+        ));
+        let mut gltf = Gltf::open(base.join("Cube.gltf")).unwrap();
+        let mut json = gltf.document.into_json();
+        json.buffers[0].uri = Some("../../../../Cargo.toml".to_owned());
+        gltf.document = Document::from_json(json).unwrap();
+
+        let options = ImportOptions::new().with_sandbox(Sandbox::base_dir());
+        GltfImporter::import_with_options(gltf, Some(base), options, |imported| {
+            match imported.unwrap_err() {
+                ImportError::Resource(error) => {
+                    assert_eq!(error.phase, ImportPhase::Buffers);
+                    assert!(matches!(error.kind, ResourceErrorKind::Forbidden));
+                }
+                error => panic!("unexpected error: {}", error),
+            }
+        });
+    }
+
+    #[test]
+    fn test_import_cube_model_in_sandbox() {
+        let base = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Cube/glTF"
+        ));
+        let gltf = Gltf::open(base.join("Cube.gltf")).unwrap();
+        let options = ImportOptions::new().with_sandbox(Sandbox::base_dir());
+        GltfImporter::import_with_options(gltf, Some(base), options, |imported| {
+            let result = imported.unwrap();
+            assert_eq!(result.images().len(), 2);
+        });
+    }
+
+    #[test]
+    fn test_import_exceeding_image_count() {
+        let base = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Cube/glTF"
+        ));
+        let gltf = Gltf::open(base.join("Cube.gltf")).unwrap();
+        let options = ImportOptions::new().with_limits(ImportLimits::new().with_max_images(1));
+        GltfImporter::import_with_options(gltf, Some(base), options, |imported| {
+            match imported.unwrap_err() {
+                ImportError::LimitExceeded(exceeded) => {
+                    assert_eq!(exceeded.limit, Limit::Images);
+                    assert_eq!(exceeded.value, 2);
+                }
+                error => panic!("unexpected error: {}", error),
+            }
+        });
+    }
+
+    /// Error of `FailingResolver`, to check that it is passed on
+    #[derive(Debug)]
+    struct ResolverError;
+
+    impl std::fmt::Display for ResolverError {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "resolver error")
+        }
+    }
+
+    impl std::error::Error for ResolverError {}
+
+    /// Resolver failing to load every resource with a `ResolverError`
+    struct FailingResolver;
+
+    impl ResourceResolver for FailingResolver {
+        fn load(&self, resources: Vec<Resource>, on_done: Box<dyn FnOnce(LoadedResources)>) {
+            let loaded = resources
+                .into_iter()
+                .map(|resource| (resource, Err(io::Error::other(ResolverError))))
+                .collect();
+            on_done(loaded)
+        }
+    }
+
+    #[test]
+    fn test_import_keeps_image_load_errors() {
+        let document = r#"{
+            "asset": { "version": "2.0" },
+            "images": [{ "uri": "single.png" }, { "uri": "shared.png" }, { "uri": "shared.png" }]
+        }"#;
+        let gltf = Gltf::from_slice(document.as_bytes()).unwrap();
+        let options = ImportOptions::new()
+            .with_resolver(FailingResolver)
+            .with_image_failures(ImageFailurePolicy::Omit);
+        GltfImporter::import_with_options(gltf, Some(PathBuf::new()), options, |imported| {
+            let result = imported.unwrap();
+            let errors: Vec<_> = result
+                .warnings()
+                .iter()
+                .map(|warning| match &warning.kind {
+                    ResourceErrorKind::Load(err) => err.get_ref().unwrap(),
+                    kind => panic!("unexpected error {}", kind),
+                })
+                .collect();
+            assert_eq!(errors.len(), 3);
+
+            assert!(errors[0].is::<ResolverError>());
+            let shared: Vec<_> = errors[1..]
+                .iter()
+                .map(|err| &err.downcast_ref::<SharedError>().unwrap().0)
+                .collect();
+            assert!(Arc::ptr_eq(shared[0], shared[1]));
+            assert!(shared[0].get_ref().unwrap().is::<ResolverError>());
+        });
+    }
+
+    /// Resolver returning 16 zero bytes for every resource, recording the size limit it has been given
+    #[derive(Clone, Default)]
+    struct OversizedResolver {
+        max_bytes: Rc<Cell<Option<usize>>>,
+    }
+
+    impl ResourceResolver for OversizedResolver {
+        fn load(&self, resources: Vec<Resource>, on_done: Box<dyn FnOnce(LoadedResources)>) {
+            let loaded = resources
+                .into_iter()
+                .map(|resource| (resource, Ok(vec![0; 16].into())))
+                .collect();
+            on_done(loaded)
+        }
+
+        fn load_with_options(
+            &self,
+            resources: Vec<Resource>,
+            options: LoadOptions,
+            on_done: Box<dyn FnOnce(LoadedResources)>,
+        ) {
+            self.max_bytes.set(options.max_bytes());
+            self.load(resources, on_done)
+        }
+    }
+
+    #[test]
+    fn test_import_exceeding_buffer_bytes() {
+        let gltf = Gltf::from_slice(
+            br#"{
+                "asset": { "version": "2.0" },
+                "buffers": [
+                    { "byteLength": 4, "uri": "data:application/octet-stream;base64,AAAAAA==" },
+                    { "byteLength": 8, "uri": "external.bin" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let resolver = OversizedResolver::default();
+        let options = ImportOptions::new()
+            .with_resolver(resolver.clone())
+            .with_limits(ImportLimits::new().with_max_buffer_bytes(12));
+        GltfImporter::import_with_options(gltf, Some(PathBuf::new()), options, |imported| {
+            match imported.unwrap_err() {
+                ImportError::Resource(error) => {
+                    assert_eq!(error.phase, ImportPhase::Buffers);
+                    assert_eq!(error.index, 1);
+                    assert!(matches!(error.kind, ResourceErrorKind::LimitExceeded(_)));
+                }
+                error => panic!("unexpected error: {}", error),
+            }
+        });
+
+        // The embedded buffer already takes 4 of the 12 bytes
+        assert_eq!(resolver.max_bytes.get(), Some(8));
+    }
+
+    #[test]
+    fn test_import_exceeding_image_limits() {
+        let base = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Cube/glTF"
+        ));
+        for limits in [
+            ImportLimits::new().with_max_image_dimension(1),
+            ImportLimits::new().with_max_image_bytes(4),
+        ]
+        .iter()
+        {
+            let gltf = Gltf::open(base.join("Cube.gltf")).unwrap();
+            let options = ImportOptions::new().with_limits(*limits);
+            GltfImporter::import_with_options(gltf, Some(base.clone()), options, |imported| {
+                match imported.unwrap_err() {
+                    ImportError::Resource(error) => {
+                        assert_eq!(error.phase, ImportPhase::Images);
+                        assert_eq!(error.index, 0);
+                        assert!(matches!(error.kind, ResourceErrorKind::LimitExceeded(_)));
+                    }
+                    error => panic!("unexpected error: {}", error),
+                }
+            });
+        }
+    }
+
+    /// Document with a texture using `EXT_texture_webp`, a 1x1 PNG image as fallback and a 1x1 WebP image
+    fn webp_texture_document() -> String {
+        format!(
+            r#"{{
+                "asset": {{ "version": "2.0" }},
+                "extensionsUsed": ["EXT_texture_webp"],
+                "textures": [{{ "source": 0, "extensions": {{ "EXT_texture_webp": {{ "source": 1 }} }} }}],
+                "images": [
+                    {{ "uri": "{png}" }},
+                    {{ "uri": "data:image/webp;base64,UklGRiIAAABXRUJQVlA4IBYAAAAwAQCdASoBAAEADsD+JaQAA3AAAAAA" }}
+                ]
+            }}"#,
+            png = PNG_DATA_URI
+        )
+    }
+
+    #[cfg(not(feature = "webp"))]
+    #[test]
+    fn test_import_webp_texture_with_fallback() {
+        GltfImporter::import_from_slice(webp_texture_document().as_bytes(), None, |imported| {
+            let result = imported.unwrap();
+            assert_eq!(result.images().len(), 1);
+            assert!(result.warnings().is_empty());
+
+            let texture = result.document().textures().next().unwrap();
+            let image = result.texture_image(&texture).unwrap();
+            assert!(std::ptr::eq(image, result.image(0).unwrap()));
+        });
+    }
+
+    #[cfg(feature = "webp")]
+    #[test]
+    fn test_import_webp_texture() {
+        GltfImporter::import_from_slice(webp_texture_document().as_bytes(), None, |imported| {
+            let result = imported.unwrap();
+            assert_eq!(result.images().len(), 2);
+
+            let texture = result.document().textures().next().unwrap();
+            let image = result.texture_image(&texture).unwrap();
+            assert!(std::ptr::eq(image, result.image(1).unwrap()));
+        });
+    }
+
+    #[test]
+    fn test_import_webp_texture_without_raw_document() {
+        let gltf = Gltf::from_slice(webp_texture_document().as_bytes()).unwrap();
+        GltfImporter::import(gltf, None, |imported| {
+            let result = imported.unwrap();
+            assert_eq!(result.unavailable_extensions(), ["EXT_texture_webp"]);
+
+            let texture = result.document().textures().next().unwrap();
+            let image = result.texture_image(&texture).unwrap();
+            assert!(std::ptr::eq(image, result.image(0).unwrap()));
+        });
+
+        let result = block_on(GltfImporter::import_from_slice_async(
+            webp_texture_document().as_bytes(),
+            None,
+        ))
+        .unwrap();
+        let texture = result.document().textures().next().unwrap();
+        let expected = if cfg!(feature = "webp") { 1 } else { 0 };
+        let image = result.texture_image(&texture).unwrap();
+        assert!(std::ptr::eq(image, result.image(expected).unwrap()));
+    }
+
+    #[test]
+    fn test_import_cube_model_with_lazy_images() {
+        let base = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Cube/glTF"
+        ));
+        let gltf = Gltf::open(base.join("Cube.gltf")).unwrap();
+        let options = ImportOptions::new().with_lazy_images(true);
+        GltfImporter::import_with_options(gltf, Some(base), options, |imported| {
+            let result = imported.unwrap();
+            assert!(result.images().is_empty());
+            assert_eq!(result.lazy_images().len(), 2);
+
+            let lazy = &result.lazy_images()[&0];
+            assert_eq!(lazy.format(), Png);
+            assert!(!lazy.is_decoded());
+            assert!(result.image(0).is_some());
+            assert!(lazy.is_decoded());
+            assert!(!result.lazy_images()[&1].is_decoded());
+            assert!(result.image(2).is_none());
+        });
+    }
+
+    type PendingLoad = (Vec<Resource>, Box<dyn FnOnce(LoadedResources)>);
+
+    /// Resolver that only loads resources once `run` is called, simulating asynchronous loading
+    #[derive(Clone, Default)]
+    struct DeferredResolver {
+        pending: Rc<RefCell<Vec<PendingLoad>>>,
+        requests: Rc<Cell<usize>>,
+    }
+
+    impl DeferredResolver {
+        fn run(&self) {
+            while self.step() {}
+        }
+
+        /// Loads the pending resources, `false` if there were none
+        fn step(&self) -> bool {
+            let next = self.pending.borrow_mut().pop();
+            match next {
+                Some((resources, on_done)) => {
+                    LoaderResolver.load(resources, on_done);
+                    true
+                }
+                None => false,
+            }
+        }
+    }
+
+    impl ResourceResolver for DeferredResolver {
+        fn load(&self, resources: Vec<Resource>, on_done: Box<dyn FnOnce(LoadedResources)>) {
+            self.requests.set(self.requests.get() + 1);
+            self.pending.borrow_mut().push((resources, on_done));
+        }
+    }
+
+    #[test]
+    fn test_import_cancelled() {
+        let base = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Cube/glTF"
+        ));
+        let gltf = Gltf::open(base.join("Cube.gltf")).unwrap();
+        let resolver = DeferredResolver::default();
+        let options = ImportOptions::new().with_resolver(resolver.clone());
+        let result = Rc::new(RefCell::new(None));
+        let handle = GltfImporter::import_with_options(gltf, Some(base), options, {
+            let result = result.clone();
+            move |imported| *result.borrow_mut() = Some(imported)
+        });
+
+        handle.cancel();
+        resolver.run();
+
+        let result = result.borrow_mut().take().unwrap();
+        assert!(matches!(result.unwrap_err(), ImportError::Cancelled));
+        // Images are not requested anymore after the buffers
+        assert_eq!(resolver.requests.get(), 1);
+    }
+
+    #[test]
+    fn test_import_cancelled_before_embedded_images() {
+        let document = format!(
+            r#"{{
+                "asset": {{ "version": "2.0" }},
+                "buffers": [{{ "byteLength": 4, "uri": "data:application/octet-stream;base64,AAAAAA==" }}],
+                "images": [
+                    {{ "uri": "{png}" }}
+                ]
+            }}"#,
+            png = PNG_DATA_URI
+        );
+        let gltf = Gltf::from_slice(document.as_bytes()).unwrap();
+        let resolver = DeferredResolver::default();
+        let options = ImportOptions::new().with_resolver(resolver.clone());
+        let result = Rc::new(RefCell::new(None));
+        let handle = GltfImporter::import_with_options(gltf, Some(PathBuf::new()), options, {
+            let result = result.clone();
+            move |imported| *result.borrow_mut() = Some(imported)
+        });
+
+        // Load the buffers, the images are requested afterwards even though all of them are embedded
+        assert!(resolver.step());
+        assert_eq!(resolver.requests.get(), 2);
+        assert!(result.borrow().is_none());
+
+        handle.cancel();
+        resolver.run();
+
+        let result = result.borrow_mut().take().unwrap();
+        assert!(matches!(result.unwrap_err(), ImportError::Cancelled));
+    }
+
+    /// Resolver dropping its callback without calling it
+    struct DroppingResolver;
+
+    impl ResourceResolver for DroppingResolver {
+        fn load(&self, _resources: Vec<Resource>, _on_done: Box<dyn FnOnce(LoadedResources)>) {}
+    }
+
+    #[test]
+    fn test_import_async_aborted_by_resolver() {
+        let document = r#"{
+            "asset": { "version": "2.0" },
+            "buffers": [{ "byteLength": 4, "uri": "buffer.bin" }]
+        }"#;
+        let gltf = Gltf::from_slice(document.as_bytes()).unwrap();
+        let options = ImportOptions::new().with_resolver(DroppingResolver);
+        let result = block_on(GltfImporter::import_async_with_options(
+            gltf,
+            Some(PathBuf::new()),
+            options,
+        ));
+        assert!(matches!(result.unwrap_err(), ImportError::Aborted));
+    }
+
+    #[test]
+    fn test_import_reports_images_until_failure() {
+        let document = format!(
+            r#"{{
+                "asset": {{ "version": "2.0" }},
+                "images": [{{ "uri": "{png}" }}, {{ "uri": "data:image/png;base64,AAAA" }}, {{ "uri": "{png}" }}]
+            }}"#,
+            png = PNG_DATA_URI
+        );
+        let gltf = Gltf::from_slice(document.as_bytes()).unwrap();
+        let finished = Rc::new(RefCell::new(Vec::new()));
+        let options = ImportOptions::new().with_progress({
+            let finished = finished.clone();
+            move |progress: &ImportProgress| {
+                if let Some(FinishedResource::Image { index, .. }) = progress.last_finished {
+                    finished.borrow_mut().push(index);
+                }
+            }
+        });
+        GltfImporter::import_with_options(gltf, None, options, |imported| match imported {
+            Err(ImportError::Resource(error)) => assert_eq!(error.index, 1),
+            _ => panic!("broken image has been imported"),
+        });
+
+        // Decoding in parallel, the last image may be done before the broken one
+        if !cfg!(feature = "parallel") {
+            assert_eq!(*finished.borrow(), vec![0]);
+        }
+        assert!(finished.borrow().contains(&0));
+    }
+
+    #[test]
+    fn test_import_fails_on_broken_embedded_image_before_loading() {
+        let document = r#"{
+            "asset": { "version": "2.0" },
+            "images": [{ "uri": "external.png" }, { "uri": "data:image/png;base64,!!!" }]
+        }"#;
+        let gltf = Gltf::from_slice(document.as_bytes()).unwrap();
+        let resolver = DeferredResolver::default();
+        let options = ImportOptions::new().with_resolver(resolver.clone());
+        let result = Rc::new(RefCell::new(None));
+        GltfImporter::import_with_options(gltf, Some(PathBuf::new()), options, {
+            let result = result.clone();
+            move |imported| *result.borrow_mut() = Some(imported)
+        });
+
+        // Load the buffers, the external image is not requested anymore
+        assert!(resolver.step());
+        assert_eq!(resolver.requests.get(), 1);
+
+        let result = result.borrow_mut().take().unwrap();
+        match result.unwrap_err() {
+            ImportError::Resource(error) => {
+                assert_eq!(error.index, 1);
+                assert!(matches!(error.kind, ResourceErrorKind::Base64(_)));
+            }
+            error => panic!("unexpected error {}", error),
+        }
+    }
+
+    #[test]
+    fn test_import_not_cancelled() {
+        let base = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Cube/glTF"
+        ));
+        let gltf = Gltf::open(base.join("Cube.gltf")).unwrap();
+        let resolver = DeferredResolver::default();
+        let options = ImportOptions::new().with_resolver(resolver.clone());
+        let result = Rc::new(RefCell::new(None));
+        let handle = GltfImporter::import_with_options(gltf, Some(base), options, {
+            let result = result.clone();
+            move |imported| *result.borrow_mut() = Some(imported)
+        });
+
+        resolver.run();
+
+        assert!(!handle.is_cancelled());
+        let result = result.borrow_mut().take().unwrap().unwrap();
+        assert_eq!(result.images().len(), 2);
+        assert_eq!(resolver.requests.get(), 2);
     }
 }
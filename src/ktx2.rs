@@ -0,0 +1,300 @@
+use std::convert::{TryFrom, TryInto};
+use std::ops::Range;
+
+/// Identifier at the start of every KTX2 file
+const IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+
+/// Size of the fixed part of the header, the level index follows it
+const HEADER_LENGTH: usize = 80;
+
+/// Size of each entry of the level index
+const LEVEL_INDEX_ENTRY_LENGTH: usize = 24;
+
+/// Whether `data` starts with the KTX2 identifier
+pub(crate) fn is_ktx2(data: &[u8]) -> bool {
+    data.starts_with(&IDENTIFIER)
+}
+
+/// Supercompression scheme of a KTX2 image
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Supercompression {
+    /// No supercompression, e.g. UASTC without Zstandard
+    None,
+    /// BasisLZ, used for ETC1S encoded Basis Universal images
+    BasisLz,
+    /// Zstandard
+    Zstandard,
+    /// ZLIB
+    Zlib,
+    /// A scheme not known to this crate
+    Other(u32),
+}
+
+impl From<u32> for Supercompression {
+    fn from(scheme: u32) -> Self {
+        match scheme {
+            0 => Supercompression::None,
+            1 => Supercompression::BasisLz,
+            2 => Supercompression::Zstandard,
+            3 => Supercompression::Zlib,
+            scheme => Supercompression::Other(scheme),
+        }
+    }
+}
+
+/// Header of a KTX2 image
+///
+/// See the [KTX 2.0 specification](https://github.khronos.org/KTX-Specification/) for the meaning of the fields.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Ktx2Header {
+    /// The Vulkan format of the image data, `0` (`VK_FORMAT_UNDEFINED`) for Basis Universal images
+    pub vk_format: u32,
+    /// Size of the data type in bytes
+    pub type_size: u32,
+    /// Width of the base level in pixels
+    pub width: u32,
+    /// Height of the base level in pixels
+    pub height: u32,
+    /// Depth of the base level in pixels, `0` for 2D images
+    pub depth: u32,
+    /// Number of array layers, `0` for images that are no arrays
+    pub layers: u32,
+    /// Number of cubemap faces, `1` for images that are no cubemaps
+    pub faces: u32,
+    /// Number of mip levels, `0` if the levels should be generated
+    pub levels: u32,
+    /// Supercompression of the level data
+    pub supercompression: Supercompression,
+}
+
+impl Ktx2Header {
+    /// Parses the header of the KTX2 image `data`, `None` if it is no valid KTX2 image
+    pub(crate) fn parse(data: &[u8]) -> Option<Self> {
+        if !is_ktx2(data) {
+            return None;
+        }
+
+        let header = Ktx2Header {
+            vk_format: read_u32(data, 12)?,
+            type_size: read_u32(data, 16)?,
+            width: read_u32(data, 20)?,
+            height: read_u32(data, 24)?,
+            depth: read_u32(data, 28)?,
+            layers: read_u32(data, 32)?,
+            faces: read_u32(data, 36)?,
+            levels: read_u32(data, 40)?,
+            supercompression: read_u32(data, 44)?.into(),
+        };
+        if header.width == 0 || header.faces == 0 {
+            return None;
+        }
+
+        // All levels have to be within the data
+        if (0..header.level_count()).any(|level| level_range(data, level).is_none()) {
+            return None;
+        }
+        Some(header)
+    }
+
+    /// Whether the image is Basis Universal encoded (ETC1S or UASTC), as required by `KHR_texture_basisu`
+    ///
+    /// Basis Universal images need to be transcoded to a format supported by the GPU before uploading them.
+    pub fn is_basis_universal(&self) -> bool {
+        self.vk_format == 0
+    }
+
+    /// Number of levels in the level index
+    fn level_count(&self) -> usize {
+        self.levels.max(1) as usize
+    }
+}
+
+/// An image in the KTX2 container format, kept compressed for transcoding and GPU upload
+///
+/// Used for textures of the `KHR_texture_basisu` extension. The images are not transcoded by this crate, use a
+/// Basis Universal transcoder (e.g. the [`basis-universal`](https://crates.io/crates/basis-universal) crate) to get
+/// a format the GPU supports.
+#[derive(Clone, Debug)]
+pub struct Ktx2Image {
+    data: Vec<u8>,
+    header: Ktx2Header,
+}
+
+impl Ktx2Image {
+    pub(crate) fn new(data: Vec<u8>, header: Ktx2Header) -> Self {
+        Self { data, header }
+    }
+
+    /// The whole KTX2 file
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// The header of the image
+    pub fn header(&self) -> &Ktx2Header {
+        &self.header
+    }
+
+    /// The (supercompressed) data of the mip level with the given index, `0` being the base level
+    pub fn level_data(&self, level: usize) -> Option<&[u8]> {
+        if level >= self.header.level_count() {
+            return None;
+        }
+        level_range(&self.data, level).map(|range| &self.data[range])
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    let bytes = data.get(offset..offset + 8)?;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+/// Range of the data of a level, `None` if it is out of bounds
+fn level_range(data: &[u8], level: usize) -> Option<Range<usize>> {
+    let entry = HEADER_LENGTH + level * LEVEL_INDEX_ENTRY_LENGTH;
+    // Offsets beyond `usize` (32-bit targets) can not be within the data
+    let offset = usize::try_from(read_u64(data, entry)?).ok()?;
+    let length = usize::try_from(read_u64(data, entry + 8)?).ok()?;
+    let end = offset.checked_add(length)?;
+
+    if end > data.len() {
+        return None;
+    }
+    Some(offset..end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::GltfImporter;
+    use crate::test_util::PNG_DATA_URI;
+    use gltf::Gltf;
+
+    /// A minimal KTX2 file with a single 4x4 level of 16 bytes
+    fn ktx2_file() -> Vec<u8> {
+        let mut data = IDENTIFIER.to_vec();
+        for value in &[0u32, 1, 4, 4, 0, 0, 1, 1, 1] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        // Data format descriptor, key/value data and supercompression global data are left empty
+        data.resize(HEADER_LENGTH, 0);
+        let offset = (HEADER_LENGTH + LEVEL_INDEX_ENTRY_LENGTH) as u64;
+        for value in &[offset, 16, 16] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&[0x55; 16]);
+        data
+    }
+
+    #[test]
+    fn test_parse_ktx2_header() {
+        let data = ktx2_file();
+        let header = Ktx2Header::parse(&data).unwrap();
+        assert_eq!(header.width, 4);
+        assert_eq!(header.height, 4);
+        assert_eq!(header.levels, 1);
+        assert_eq!(header.supercompression, Supercompression::BasisLz);
+        assert!(header.is_basis_universal());
+
+        let image = Ktx2Image::new(data, header);
+        assert_eq!(image.level_data(0), Some(&[0x55; 16][..]));
+        assert_eq!(image.level_data(1), None);
+    }
+
+    #[test]
+    fn test_import_ktx2_image() {
+        let json = format!(
+            r#"{{
+                "asset": {{ "version": "2.0" }},
+                "images": [{{ "uri": "data:image/ktx2;base64,{}" }}]
+            }}"#,
+            base64::encode(&ktx2_file())
+        );
+        let gltf = Gltf::from_slice(json.as_bytes()).unwrap();
+        GltfImporter::import(gltf, None, |imported| {
+            let result = imported.unwrap();
+            assert!(result.images().is_empty());
+            assert!(result.image(0).is_none());
+
+            let image = &result.compressed_images()[&0];
+            assert_eq!(image.header().width, 4);
+            assert_eq!(image.level_data(0).map(<[u8]>::len), Some(16));
+        });
+    }
+
+    /// Document with a texture using `KHR_texture_basisu`, with `fallback` as additional `source` of the texture
+    fn basisu_texture_document(fallback: bool) -> String {
+        let source = if fallback { r#""source": 1,"# } else { "" };
+        format!(
+            r#"{{
+                "asset": {{ "version": "2.0" }},
+                "extensionsUsed": ["KHR_texture_basisu"],
+                "textures": [{{ {} "extensions": {{ "KHR_texture_basisu": {{ "source": 0 }} }} }}],
+                "images": [
+                    {{ "uri": "data:image/ktx2;base64,{}" }},
+                    {{ "uri": "{png}" }}
+                ]
+            }}"#,
+            source,
+            base64::encode(&ktx2_file()),
+            png = PNG_DATA_URI
+        )
+    }
+
+    #[test]
+    fn test_import_basisu_texture_without_fallback() {
+        let json = basisu_texture_document(false);
+        assert!(Gltf::from_slice(json.as_bytes()).is_err());
+
+        GltfImporter::import_from_slice(json.as_bytes(), None, |imported| {
+            let result = imported.unwrap();
+            let texture = result.document().textures().next().unwrap();
+            assert_eq!(texture.source().index(), 0);
+
+            let image = result.texture_compressed_image(&texture).unwrap();
+            assert_eq!(image.header().width, 4);
+            assert!(result.unavailable_extensions().is_empty());
+        });
+    }
+
+    #[test]
+    fn test_import_basisu_texture_with_fallback() {
+        let json = basisu_texture_document(true);
+        GltfImporter::import_from_slice(json.as_bytes(), None, |imported| {
+            let result = imported.unwrap();
+            let texture = result.document().textures().next().unwrap();
+            assert!(result.image(texture.source().index()).is_some());
+            assert!(result.texture_compressed_image(&texture).is_some());
+            assert!(result.texture(0).unwrap().compressed_image.is_some());
+        });
+
+        // The extension is not known without the raw document
+        let gltf = Gltf::from_slice(json.as_bytes()).unwrap();
+        GltfImporter::import(gltf, None, |imported| {
+            let result = imported.unwrap();
+            let texture = result.document().textures().next().unwrap();
+            assert!(result.texture_compressed_image(&texture).is_none());
+            assert_eq!(result.unavailable_extensions(), ["KHR_texture_basisu"]);
+        });
+    }
+
+    #[test]
+    fn test_parse_invalid_ktx2_header() {
+        let data = ktx2_file();
+        assert!(Ktx2Header::parse(&data[..HEADER_LENGTH]).is_none());
+        assert!(Ktx2Header::parse(&data[..data.len() - 1]).is_none());
+        assert!(Ktx2Header::parse(&data[1..]).is_none());
+
+        // Level offset beyond 4 GiB, which must not wrap around to the start of the data on 32-bit targets
+        let mut data = data;
+        data[HEADER_LENGTH + 4..HEADER_LENGTH + 8].copy_from_slice(&1u32.to_le_bytes());
+        assert!(Ktx2Header::parse(&data).is_none());
+    }
+}
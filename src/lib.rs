@@ -4,4 +4,20 @@ extern crate base64;
 extern crate gltf;
 extern crate three_d;
 
+pub mod cache;
+pub mod camera;
+pub mod error;
+mod extensions;
 pub mod import;
+pub mod ktx2;
+pub mod light;
+pub mod limits;
+pub mod material;
+pub mod mesh;
+pub mod progress;
+pub mod resolver;
+pub mod sandbox;
+pub mod scene;
+pub mod texture;
+#[cfg(test)]
+mod test_util;
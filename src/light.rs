@@ -0,0 +1,264 @@
+use crate::import::ImportedGltfModel;
+use crate::scene::scene_nodes;
+use gltf::khr_lights_punctual::Kind;
+use three_d::Vec3;
+
+/// A light of the `KHR_lights_punctual` extension placed in a scene by a node, in world space
+///
+/// The values correspond to the parameters of `three-d`'s `DirectionalLight`, `PointLight` and `SpotLight`, except for
+/// the cone of spot lights, see [`LightKind::three_d_cutoff`](enum.LightKind.html#method.three_d_cutoff).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportedLight {
+    /// Index of the light from the `KHR_lights_punctual` extension of the GLTF document
+    pub light: usize,
+    /// Index of the node referencing the light
+    pub node: usize,
+    /// Name of the light, if any
+    pub name: Option<String>,
+    /// Linear RGB color of the light
+    pub color: Vec3,
+    /// Brightness of the light, in lux (lm/m²) for directional lights and candela (lm/sr) for point and spot lights
+    ///
+    /// Physical units are not used by `three-d`, scale the intensity to fit the scene.
+    pub intensity: f32,
+    /// Type of the light, with its world position and direction
+    pub kind: LightKind,
+}
+
+/// Type of a punctual light
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LightKind {
+    /// Light infinitely far away, e.g. the sun
+    Directional {
+        /// Direction the light shines in (normalized)
+        direction: Vec3,
+    },
+    /// Light emitting in all directions from its position
+    Point {
+        /// Position of the light
+        position: Vec3,
+        /// Distance after which the light is cut off, `None` if it is unlimited
+        range: Option<f32>,
+        /// Attenuation of the light with the distance
+        attenuation: Attenuation,
+    },
+    /// Light emitting in a cone from its position
+    Spot {
+        /// Position of the light
+        position: Vec3,
+        /// Direction of the cone (normalized)
+        direction: Vec3,
+        /// Distance after which the light is cut off, `None` if it is unlimited
+        range: Option<f32>,
+        /// Attenuation of the light with the distance
+        attenuation: Attenuation,
+        /// Angle from the center of the cone where the light starts to fall off, in radians
+        inner_cone_angle: f32,
+        /// Angle from the center of the cone where the light ends, in radians
+        ///
+        /// `three-d` spot lights have a single cutoff angle in degrees, see
+        /// [`three_d_cutoff`](#method.three_d_cutoff).
+        outer_cone_angle: f32,
+    },
+}
+
+impl LightKind {
+    /// The cutoff angle of a spot light in degrees, as taken by `three_d::SpotLight::new`
+    ///
+    /// This is the outer cone angle, `three-d` lets the light fall off within the outer quarter of the cone instead
+    /// of from the inner cone angle. `None` for directional and point lights.
+    pub fn three_d_cutoff(&self) -> Option<f32> {
+        match *self {
+            LightKind::Spot {
+                outer_cone_angle, ..
+            } => Some(outer_cone_angle.to_degrees()),
+            LightKind::Directional { .. } | LightKind::Point { .. } => None,
+        }
+    }
+}
+
+/// Attenuation of a point or spot light, `1 / (constant + linear * d + exponential * d²)` at distance `d`
+///
+/// GLTF lights attenuate with the inverse square of the distance. The constant term of `1` keeps the light finite at
+/// its position, and lights with a range are attenuated to 1/256 of their intensity at the range.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Attenuation {
+    /// Constant attenuation
+    pub constant: f32,
+    /// Attenuation linear to the distance
+    pub linear: f32,
+    /// Attenuation quadratic to the distance
+    pub exponential: f32,
+}
+
+impl Attenuation {
+    fn new(range: Option<f32>) -> Self {
+        Attenuation {
+            constant: 1.0,
+            linear: 0.0,
+            exponential: match range {
+                Some(range) if range > 0.0 => 255.0 / (range * range),
+                _ => 1.0,
+            },
+        }
+    }
+}
+
+impl ImportedGltfModel {
+    /// All lights of the [default scene](#method.default_scene)
+    ///
+    /// Lights referenced by multiple nodes are returned once per node. Empty if the document has no scenes.
+    pub fn lights(&self) -> Vec<ImportedLight> {
+        self.default_scene()
+            .and_then(|scene| self.lights_in_scene(scene.index))
+            .unwrap_or_default()
+    }
+
+    /// All lights of the scene with the given index from the `scenes` section of the GLTF document
+    ///
+    /// Directional and spot lights shine along the negative Z axis of their node. Positions and directions are taken
+    /// from the world transformation of the node (see [`scene_instances`](#method.scene_instances)), the scale of the
+    /// node does not affect the range or cone of the light. Directional and spot lights of nodes with a zero scale
+    /// have no direction and are left out. `None` if there is no such scene.
+    pub fn lights_in_scene(&self, scene: usize) -> Option<Vec<ImportedLight>> {
+        let scene = self.document().scenes().nth(scene)?;
+        Some(
+            scene_nodes(&scene)
+                .into_iter()
+                .filter_map(|scene_node| {
+                    let light = scene_node.node.light()?;
+                    let direction = || scene_node.direction(Vec3::new(0.0, 0.0, -1.0));
+                    let range = light.range();
+
+                    let kind = match light.kind() {
+                        Kind::Directional => LightKind::Directional {
+                            direction: direction()?,
+                        },
+                        Kind::Point => LightKind::Point {
+                            position: scene_node.position(),
+                            range,
+                            attenuation: Attenuation::new(range),
+                        },
+                        Kind::Spot {
+                            inner_cone_angle,
+                            outer_cone_angle,
+                        } => LightKind::Spot {
+                            position: scene_node.position(),
+                            direction: direction()?,
+                            range,
+                            attenuation: Attenuation::new(range),
+                            inner_cone_angle,
+                            outer_cone_angle,
+                        },
+                    };
+
+                    Some(ImportedLight {
+                        light: light.index(),
+                        node: scene_node.node.index(),
+                        name: light.name().map(String::from),
+                        color: light.color().into(),
+                        intensity: light.intensity(),
+                        kind,
+                    })
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::GltfImporter;
+    use three_d::InnerSpace;
+
+    #[test]
+    fn test_import_lights() {
+        let document = r#"{
+            "asset": { "version": "2.0" },
+            "extensionsUsed": ["KHR_lights_punctual"],
+            "extensions": {
+                "KHR_lights_punctual": {
+                    "lights": [
+                        { "name": "Sun", "type": "directional", "color": [1, 0.9, 0.8], "intensity": 3 },
+                        { "type": "point", "range": 16 },
+                        { "type": "spot", "spot": { "innerConeAngle": 0.2, "outerConeAngle": 0.5 } },
+                        { "type": "spot", "spot": { "outerConeAngle": 0.7853982 } }
+                    ]
+                }
+            },
+            "nodes": [
+                { "rotation": [-0.70710677, 0, 0, 0.70710677], "extensions": { "KHR_lights_punctual": { "light": 0 } } },
+                { "translation": [1, 2, 3], "children": [2, 3] },
+                { "scale": [4, 4, 4], "extensions": { "KHR_lights_punctual": { "light": 1 } } },
+                { "translation": [0, 0, 1], "extensions": { "KHR_lights_punctual": { "light": 2 } } },
+                { "scale": [0, 0, 0], "children": [5, 6] },
+                { "extensions": { "KHR_lights_punctual": { "light": 0 } } },
+                { "extensions": { "KHR_lights_punctual": { "light": 1 } } },
+                { "extensions": { "KHR_lights_punctual": { "light": 3 } } }
+            ],
+            "scenes": [{ "nodes": [0, 1] }, { "nodes": [4] }, { "nodes": [7] }]
+        }"#;
+        GltfImporter::import_from_slice(document.as_bytes(), None, |imported| {
+            let result = imported.unwrap();
+            let lights = result.lights();
+            assert_eq!(lights.len(), 3);
+
+            // Rotated by -90° around the X axis, thus shining down
+            let sun = &lights[0];
+            assert_eq!(sun.name.as_deref(), Some("Sun"));
+            assert_eq!(sun.color, Vec3::new(1.0, 0.9, 0.8));
+            assert_eq!(sun.intensity, 3.0);
+            match sun.kind {
+                LightKind::Directional { direction } => {
+                    assert!((direction - Vec3::new(0.0, -1.0, 0.0)).magnitude() < 1e-6)
+                }
+                kind => panic!("unexpected light {:?}", kind),
+            }
+
+            let point = &lights[1];
+            assert_eq!(point.node, 2);
+            assert_eq!(point.color, Vec3::new(1.0, 1.0, 1.0));
+            assert_eq!(point.intensity, 1.0);
+            assert_eq!(
+                point.kind,
+                LightKind::Point {
+                    position: Vec3::new(1.0, 2.0, 3.0),
+                    range: Some(16.0),
+                    attenuation: Attenuation {
+                        constant: 1.0,
+                        linear: 0.0,
+                        exponential: 255.0 / 256.0,
+                    },
+                }
+            );
+
+            assert_eq!(
+                lights[2].kind,
+                LightKind::Spot {
+                    position: Vec3::new(1.0, 2.0, 4.0),
+                    direction: Vec3::new(0.0, 0.0, -1.0),
+                    range: None,
+                    attenuation: Attenuation::new(None),
+                    inner_cone_angle: 0.2,
+                    outer_cone_angle: 0.5,
+                }
+            );
+            assert_eq!(lights[2].kind.three_d_cutoff(), Some(0.5f32.to_degrees()));
+            assert!(point.kind.three_d_cutoff().is_none());
+
+            // A zero scale leaves the point light in place, but the directional light has no direction
+            let hidden = result.lights_in_scene(1).unwrap();
+            assert_eq!(hidden.len(), 1);
+            assert_eq!(hidden[0].node, 6);
+            assert!(
+                matches!(hidden[0].kind, LightKind::Point { position, .. } if position == Vec3::new(0.0, 0.0, 0.0))
+            );
+
+            // A cone of 45° (π/4)
+            let spot = &result.lights_in_scene(2).unwrap()[0];
+            let cutoff = spot.kind.three_d_cutoff().unwrap();
+            assert!((cutoff - 45.0).abs() < 1e-4);
+        });
+    }
+}
@@ -0,0 +1,221 @@
+use gltf::Document;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+
+/// Limits for the resources an import may load and decode
+///
+/// Set via [`ImportOptions::with_limits`](../import/struct.ImportOptions.html#method.with_limits), e.g. when
+/// importing untrusted (user uploaded) models. By default, nothing is limited.
+///
+/// ```rust
+/// use three_d_gltf_import::import::ImportOptions;
+/// use three_d_gltf_import::limits::ImportLimits;
+///
+/// let limits = ImportLimits::new()
+///     .with_max_buffer_bytes(64 * 1024 * 1024)
+///     .with_max_images(32)
+///     .with_max_image_dimension(4096)
+///     .with_max_image_bytes(256 * 1024 * 1024);
+/// let options = ImportOptions::new().with_limits(limits);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ImportLimits {
+    max_buffers: Option<usize>,
+    max_buffer_bytes: Option<usize>,
+    max_images: Option<usize>,
+    max_image_dimension: Option<u32>,
+    max_image_bytes: Option<usize>,
+}
+
+impl ImportLimits {
+    /// No limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the number of buffers in the document
+    pub fn with_max_buffers(mut self, max: usize) -> Self {
+        self.max_buffers = Some(max);
+        self
+    }
+
+    /// Limits the total size of all buffers in bytes
+    ///
+    /// Checked against the sizes declared in the document before anything is loaded and against the actual size of
+    /// each buffer as it arrives. The remaining budget is passed to the resolver, so that the default resolver
+    /// rejects larger files without reading them.
+    pub fn with_max_buffer_bytes(mut self, max: usize) -> Self {
+        self.max_buffer_bytes = Some(max);
+        self
+    }
+
+    /// Limits the number of images in the document
+    pub fn with_max_images(mut self, max: usize) -> Self {
+        self.max_images = Some(max);
+        self
+    }
+
+    /// Limits the width and height of each image in pixels
+    ///
+    /// Checked before the image is decoded.
+    pub fn with_max_image_dimension(mut self, max: u32) -> Self {
+        self.max_image_dimension = Some(max);
+        self
+    }
+
+    /// Limits the memory of all decoded images in bytes
+    ///
+    /// Estimated with 4 bytes (RGBA) per pixel before each image is decoded.
+    pub fn with_max_image_bytes(mut self, max: usize) -> Self {
+        self.max_image_bytes = Some(max);
+        self
+    }
+
+    pub(crate) fn check_document(&self, document: &Document) -> Result<(), LimitExceeded> {
+        check(Limit::Buffers, document.buffers().len(), self.max_buffers)?;
+        // Declared lengths are summed as `u64`, a `usize` overflows on 32-bit targets for hostile documents
+        let buffer_bytes = document.buffers().fold(0u64, |sum, buffer| {
+            sum.saturating_add(buffer.length() as u64)
+        });
+        match self.max_buffer_bytes {
+            Some(max) if buffer_bytes > max as u64 => {
+                return Err(LimitExceeded {
+                    limit: Limit::BufferBytes,
+                    value: usize::try_from(buffer_bytes).unwrap_or(usize::MAX),
+                    max,
+                })
+            }
+            _ => {}
+        }
+        check(Limit::Images, document.images().len(), self.max_images)
+    }
+
+    pub(crate) fn max_buffer_bytes(&self) -> Option<usize> {
+        self.max_buffer_bytes
+    }
+
+    pub(crate) fn check_buffer_bytes(&self, bytes: usize) -> Result<(), LimitExceeded> {
+        check(Limit::BufferBytes, bytes, self.max_buffer_bytes)
+    }
+
+    /// Whether the dimensions of images need to be checked before decoding
+    pub(crate) fn limits_images(&self) -> bool {
+        self.max_image_dimension.is_some() || self.max_image_bytes.is_some()
+    }
+
+    pub(crate) fn check_image_dimensions(
+        &self,
+        width: u32,
+        height: u32,
+    ) -> Result<(), LimitExceeded> {
+        check(
+            Limit::ImageDimension,
+            width.max(height) as usize,
+            self.max_image_dimension.map(|max| max as usize),
+        )
+    }
+
+    pub(crate) fn check_image_bytes(&self, bytes: usize) -> Result<(), LimitExceeded> {
+        check(Limit::ImageBytes, bytes, self.max_image_bytes)
+    }
+}
+
+fn check(limit: Limit, value: usize, max: Option<usize>) -> Result<(), LimitExceeded> {
+    match max {
+        Some(max) if value > max => Err(LimitExceeded { limit, value, max }),
+        _ => Ok(()),
+    }
+}
+
+/// A limit of [`ImportLimits`](struct.ImportLimits.html)
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Limit {
+    /// Number of buffers
+    Buffers,
+    /// Total size of all buffers in bytes
+    BufferBytes,
+    /// Number of images
+    Images,
+    /// Width or height of an image in pixels
+    ImageDimension,
+    /// Total memory of all decoded images in bytes
+    ImageBytes,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Limit::Buffers => write!(f, "number of buffers"),
+            Limit::BufferBytes => write!(f, "buffer bytes"),
+            Limit::Images => write!(f, "number of images"),
+            Limit::ImageDimension => write!(f, "image dimension"),
+            Limit::ImageBytes => write!(f, "image bytes"),
+        }
+    }
+}
+
+/// A limit of [`ImportLimits`](struct.ImportLimits.html) has been exceeded
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct LimitExceeded {
+    /// The exceeded limit
+    pub limit: Limit,
+    /// The value exceeding the limit
+    pub value: usize,
+    /// The configured maximum
+    pub max: usize,
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} exceeds the limit of {}",
+            self.limit, self.value, self.max
+        )
+    }
+}
+
+impl Error for LimitExceeded {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gltf::Gltf;
+
+    #[test]
+    fn test_check_limits() {
+        let limits = ImportLimits::new()
+            .with_max_buffer_bytes(1024)
+            .with_max_image_dimension(512);
+        assert!(limits.check_buffer_bytes(1024).is_ok());
+        assert_eq!(
+            limits.check_buffer_bytes(1025),
+            Err(LimitExceeded {
+                limit: Limit::BufferBytes,
+                value: 1025,
+                max: 1024
+            })
+        );
+        assert!(limits.check_image_dimensions(512, 256).is_ok());
+        assert!(limits.check_image_dimensions(256, 513).is_err());
+        assert!(limits.check_image_bytes(usize::MAX).is_ok());
+        assert!(ImportLimits::new().check_buffer_bytes(usize::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_check_large_declared_buffers() {
+        let gltf = Gltf::from_slice(
+            br#"{
+                "asset": { "version": "2.0" },
+                "buffers": [{ "byteLength": 3000000000 }, { "byteLength": 3000000000 }]
+            }"#,
+        )
+        .unwrap();
+        let limits = ImportLimits::new().with_max_buffer_bytes(u32::MAX as usize);
+        let exceeded = limits.check_document(&gltf.document).unwrap_err();
+        assert_eq!(exceeded.limit, Limit::BufferBytes);
+        assert!(exceeded.value > exceeded.max);
+        assert!(ImportLimits::new().check_document(&gltf.document).is_ok());
+    }
+}
@@ -0,0 +1,311 @@
+use crate::import::ImportedGltfModel;
+use crate::texture::{TextureSlot, TextureTransform};
+use gltf::texture;
+use gltf::Material;
+use std::collections::HashMap;
+use std::fmt;
+use three_d::{CPUMaterial, CPUTexture};
+
+/// Converted materials
+///
+/// Keys of the hashmap corresponds to the indexes from the `materials` section of the GLTF document
+pub type ImportedMaterials = HashMap<usize, ImportedMaterial>;
+
+/// A GLTF metallic-roughness material, converted for `three-d`
+///
+/// `three-d`'s `CPUMaterial` only covers the base color and (Phong) shading parameters, thus the remaining PBR
+/// properties of the GLTF material are kept alongside it.
+pub struct ImportedMaterial {
+    /// Name of the material in the GLTF document, for display purposes
+    ///
+    /// GLTF material names are not unique, `cpu_material.name` is thus derived from the material index instead
+    /// (see [`material_name`](fn.material_name.html))
+    pub name: Option<String>,
+    /// The `three-d` material with the base color (factor and texture)
+    ///
+    /// Diffuse intensity, specular intensity and specular power are approximated from the metallic and roughness factors
+    pub cpu_material: CPUMaterial,
+    /// Metalness of the material, from 0.0 (dielectric) to 1.0 (metal)
+    pub metallic_factor: f32,
+    /// Roughness of the material, from 0.0 (smooth) to 1.0 (rough)
+    pub roughness_factor: f32,
+    /// Metalness (blue channel) and roughness (green channel) texture
+    pub metallic_roughness_texture: Option<CPUTexture<u8>>,
+    /// Tangent space normal texture
+    pub normal_texture: Option<CPUTexture<u8>>,
+    /// Scale applied to the normals read from `normal_texture`
+    pub normal_scale: f32,
+    /// Ambient occlusion texture (red channel)
+    pub occlusion_texture: Option<CPUTexture<u8>>,
+    /// Strength of the ambient occlusion, from 0.0 (no occlusion) to 1.0 (full occlusion)
+    pub occlusion_strength: f32,
+    /// Emitted RGB color
+    pub emissive_factor: [f32; 3],
+    /// Emissive color texture
+    pub emissive_texture: Option<CPUTexture<u8>>,
+    /// Transformations of the texture coordinates of the texture slots using `KHR_texture_transform`
+    ///
+    /// The transformations are not applied to the textures. Apply them when sampling the textures, or bake them into
+    /// the meshes via [`ImportedGltfModel::cpu_meshes_with_texture_transforms`](../import/struct.ImportedGltfModel.html#method.cpu_meshes_with_texture_transforms).
+    /// The extension is only known for some imports, see [extensions](../import/struct.ImportedGltfModel.html#extensions).
+    pub texture_transforms: HashMap<TextureSlot, TextureTransform>,
+}
+
+// `three-d` does not implement `Debug` for its materials and textures, textures are thus only described by their size
+impl fmt::Debug for ImportedMaterial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImportedMaterial")
+            .field("name", &self.name)
+            .field("cpu_material", &DebugMaterial(&self.cpu_material))
+            .field("metallic_factor", &self.metallic_factor)
+            .field("roughness_factor", &self.roughness_factor)
+            .field(
+                "metallic_roughness_texture",
+                &self.metallic_roughness_texture.as_ref().map(DebugTexture),
+            )
+            .field(
+                "normal_texture",
+                &self.normal_texture.as_ref().map(DebugTexture),
+            )
+            .field("normal_scale", &self.normal_scale)
+            .field(
+                "occlusion_texture",
+                &self.occlusion_texture.as_ref().map(DebugTexture),
+            )
+            .field("occlusion_strength", &self.occlusion_strength)
+            .field("emissive_factor", &self.emissive_factor)
+            .field(
+                "emissive_texture",
+                &self.emissive_texture.as_ref().map(DebugTexture),
+            )
+            .finish()
+    }
+}
+
+struct DebugMaterial<'a>(&'a CPUMaterial);
+
+impl fmt::Debug for DebugMaterial<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let material = self.0;
+        f.debug_struct("CPUMaterial")
+            .field("name", &material.name)
+            .field("color", &material.color)
+            .field(
+                "texture_image",
+                &material.texture_image.as_ref().map(DebugTexture),
+            )
+            .field("diffuse_intensity", &material.diffuse_intensity)
+            .field("specular_intensity", &material.specular_intensity)
+            .field("specular_power", &material.specular_power)
+            .finish()
+    }
+}
+
+struct DebugTexture<'a>(&'a CPUTexture<u8>);
+
+impl fmt::Debug for DebugTexture<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CPUTexture")
+            .field("width", &self.0.width)
+            .field("height", &self.0.height)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ImportedGltfModel {
+    /// Converts all materials of the document
+    ///
+    /// Textures are taken from the imported images, with the filtering and wrapping of their samplers. Materials
+    /// referencing missing images are converted without the respective texture.
+    pub fn materials(&self) -> ImportedMaterials {
+        self.document()
+            .materials()
+            .filter_map(|material| {
+                let index = material.index()?;
+                Some((index, self.convert_material(&material)))
+            })
+            .collect()
+    }
+
+    /// Converts the materials used by the meshes of the scene with the given index from the `scenes` section of the
+    /// GLTF document
+    ///
+    /// Same as [`materials`](#method.materials), but leaves out materials that are not used by any mesh of the
+    /// scene. `None` if there is no such scene.
+    pub fn materials_in_scene(&self, scene: usize) -> Option<ImportedMaterials> {
+        Some(
+            self.scene_meshes(scene)?
+                .iter()
+                .flat_map(|mesh| mesh.primitives())
+                .filter_map(|primitive| {
+                    let material = primitive.material();
+                    let index = material.index()?;
+                    Some((index, material))
+                })
+                .collect::<HashMap<_, _>>()
+                .into_iter()
+                .map(|(index, material)| (index, self.convert_material(&material)))
+                .collect(),
+        )
+    }
+
+    /// Converts the material with the given index from the `materials` section of the GLTF document
+    pub fn material(&self, index: usize) -> Option<ImportedMaterial> {
+        self.document()
+            .materials()
+            .nth(index)
+            .map(|material| self.convert_material(&material))
+    }
+
+    fn convert_material(&self, material: &Material) -> ImportedMaterial {
+        let pbr = material.pbr_metallic_roughness();
+        let [red, green, blue, alpha] = pbr.base_color_factor();
+        let metallic_factor = pbr.metallic_factor();
+        let roughness_factor = pbr.roughness_factor();
+
+        ImportedMaterial {
+            name: material.name().map(|name| name.to_owned()),
+            cpu_material: CPUMaterial {
+                name: material_name(material).unwrap_or_default(),
+                color: Some((red, green, blue, alpha)),
+                texture_image: pbr
+                    .base_color_texture()
+                    .and_then(|info| self.texture_to_cpu_texture(&info.texture())),
+                diffuse_intensity: Some(1.0 - metallic_factor),
+                specular_intensity: Some(1.0 - roughness_factor),
+                specular_power: Some(roughness_to_specular_power(roughness_factor)),
+            },
+            metallic_factor,
+            roughness_factor,
+            metallic_roughness_texture: pbr
+                .metallic_roughness_texture()
+                .and_then(|info| self.texture_to_cpu_texture(&info.texture())),
+            normal_texture: material
+                .normal_texture()
+                .and_then(|normal| self.texture_to_cpu_texture(&normal.texture())),
+            normal_scale: material
+                .normal_texture()
+                .map_or(1.0, |normal| normal.scale()),
+            occlusion_texture: material
+                .occlusion_texture()
+                .and_then(|occlusion| self.texture_to_cpu_texture(&occlusion.texture())),
+            occlusion_strength: material
+                .occlusion_texture()
+                .map_or(1.0, |occlusion| occlusion.strength()),
+            emissive_factor: material.emissive_factor(),
+            emissive_texture: material
+                .emissive_texture()
+                .and_then(|info| self.texture_to_cpu_texture(&info.texture())),
+            texture_transforms: texture_slots(material)
+                .into_iter()
+                .filter_map(|(slot, _)| Some((slot, self.texture_transform(material, slot)?)))
+                .collect(),
+        }
+    }
+
+    /// The `KHR_texture_transform` of the given texture slot of `material`
+    pub(crate) fn texture_transform(
+        &self,
+        material: &Material,
+        slot: TextureSlot,
+    ) -> Option<TextureTransform> {
+        self.extensions().texture_transform(material.index()?, slot)
+    }
+
+    fn texture_to_cpu_texture(&self, texture: &texture::Texture) -> Option<CPUTexture<u8>> {
+        self.texture(texture.index())
+            .map(|texture| texture.to_cpu_texture())
+    }
+}
+
+/// Key of a material, as used for `CPUMaterial::name` and `CPUMesh::material_name`
+///
+/// Always `material_<index>`, since names in the GLTF document may be missing or collide. The default material
+/// has no key.
+pub fn material_name(material: &Material) -> Option<String> {
+    material.index().map(|index| format!("material_{}", index))
+}
+
+/// The texture slots used by `material`, with the set of texture coordinates they use
+pub(crate) fn texture_slots(material: &Material) -> Vec<(TextureSlot, u32)> {
+    let pbr = material.pbr_metallic_roughness();
+    vec![
+        pbr.base_color_texture()
+            .map(|info| (TextureSlot::BaseColor, info.tex_coord())),
+        pbr.metallic_roughness_texture()
+            .map(|info| (TextureSlot::MetallicRoughness, info.tex_coord())),
+        material
+            .normal_texture()
+            .map(|normal| (TextureSlot::Normal, normal.tex_coord())),
+        material
+            .occlusion_texture()
+            .map(|occlusion| (TextureSlot::Occlusion, occlusion.tex_coord())),
+        material
+            .emissive_texture()
+            .map(|info| (TextureSlot::Emissive, info.tex_coord())),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Approximates a Blinn-Phong specular exponent for the given roughness
+fn roughness_to_specular_power(roughness: f32) -> f32 {
+    let alpha = roughness * roughness;
+    (2.0 / (alpha * alpha).max(1e-4) - 2.0).clamp(1.0, 512.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::GltfImporter;
+    use gltf::Gltf;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_convert_cube_materials() {
+        let base = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Cube/glTF"
+        ));
+        let gltf = Gltf::open(base.join("Cube.gltf")).unwrap();
+        GltfImporter::import(gltf, Some(base), |imported| {
+            let result = imported.unwrap();
+            let materials = result.materials();
+            assert_eq!(materials.len(), 1);
+
+            let cube = &materials[&0];
+            assert_eq!(cube.name.as_deref(), Some("Cube"));
+            assert_eq!(cube.cpu_material.name, "material_0");
+            assert!(cube.cpu_material.texture_image.is_some());
+            assert!(cube.metallic_roughness_texture.is_some());
+            assert!(cube.normal_texture.is_none());
+
+            let meshes = result.cpu_meshes();
+            assert_eq!(meshes[&(0, 0)].material_name.as_deref(), Some("material_0"));
+        });
+    }
+
+    #[test]
+    fn test_convert_model_without_materials() {
+        let base = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Triangle/glTF"
+        ));
+        let gltf = Gltf::open(base.join("Triangle.gltf")).unwrap();
+        GltfImporter::import(gltf, Some(base), |imported| {
+            let result = imported.unwrap();
+            assert!(result.materials().is_empty());
+            assert_eq!(result.cpu_meshes()[&(0, 0)].material_name, None);
+        });
+    }
+
+    #[test]
+    fn test_roughness_to_specular_power() {
+        assert_eq!(roughness_to_specular_power(1.0), 1.0);
+        assert_eq!(roughness_to_specular_power(0.0), 512.0);
+        assert_eq!(roughness_to_specular_power(0.5), 30.0);
+    }
+}
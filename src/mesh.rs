@@ -0,0 +1,507 @@
+use crate::error::{ImportError, ImportPhase, ResourceError, ResourceErrorKind, Result};
+use crate::import::{ImportedGltfModel, LoadedBuffers};
+use crate::material::{material_name, texture_slots};
+use crate::texture::TextureTransform;
+use gltf::accessor::{DataType, Dimensions};
+use gltf::buffer::View;
+use gltf::json::{validation, Path};
+use gltf::mesh::{Mode, Semantic};
+use gltf::{Accessor, Document, Error, Material, Mesh, Primitive};
+use std::collections::HashMap;
+use three_d::CPUMesh;
+
+/// Converted mesh primitives
+///
+/// Keys of the hashmap are `(mesh index, primitive index)` pairs, corresponding to the indexes from the `meshes`
+/// section of the GLTF document and the index of the primitive within that mesh
+pub type CPUMeshes = HashMap<(usize, usize), CPUMesh>;
+
+impl ImportedGltfModel {
+    /// Converts the primitives of all meshes in the document into `three-d` meshes
+    ///
+    /// The `POSITION`, `NORMAL` and `TEXCOORD_0` attributes as well as the indices of every primitive are read from
+    /// the imported buffers. Primitives without positions or with a non-triangle mode (points, lines) are skipped.
+    ///
+    /// ```rust
+    /// use gltf::Gltf;
+    /// use std::path::PathBuf;
+    /// use three_d_gltf_import::import::GltfImporter;
+    ///
+    /// let base = PathBuf::from("./sample_models/2.0/Triangle/glTF");
+    /// let gltf = Gltf::open(base.join("Triangle.gltf")).unwrap();
+    /// GltfImporter::import(gltf, Some(base), |imported| {
+    ///     let meshes = imported.unwrap().cpu_meshes();
+    ///     assert_eq!(meshes[&(0, 0)].positions.len(), 9);
+    /// });
+    /// ```
+    pub fn cpu_meshes(&self) -> CPUMeshes {
+        self.convert_meshes(self.document().meshes(), false)
+    }
+
+    /// Converts the primitives of the meshes used by the scene with the given index from the `scenes` section of the
+    /// GLTF document
+    ///
+    /// Same as [`cpu_meshes`](#method.cpu_meshes), but leaves out meshes that are not referenced by any node of the
+    /// scene. `None` if there is no such scene.
+    pub fn cpu_meshes_in_scene(&self, scene: usize) -> Option<CPUMeshes> {
+        let meshes = self.scene_meshes(scene)?;
+        Some(self.convert_meshes(meshes.into_iter(), false))
+    }
+
+    /// Converts the primitives of all meshes in the document, with the `KHR_texture_transform` of their materials
+    /// baked into the texture coordinates
+    ///
+    /// The transformation is only baked if all texture slots of the material use the first set of texture
+    /// coordinates with the same transformation, otherwise the texture coordinates are kept as they are (see
+    /// [`ImportedMaterial::texture_transforms`](../material/struct.ImportedMaterial.html#structfield.texture_transforms)).
+    /// Besides that, this is the same as [`cpu_meshes`](#method.cpu_meshes).
+    ///
+    /// The transformations are only known for some imports, see
+    /// [extensions](../import/struct.ImportedGltfModel.html#extensions).
+    pub fn cpu_meshes_with_texture_transforms(&self) -> CPUMeshes {
+        self.convert_meshes(self.document().meshes(), true)
+    }
+
+    fn convert_meshes<'a>(
+        &'a self,
+        meshes: impl Iterator<Item = Mesh<'a>>,
+        bake_texture_transforms: bool,
+    ) -> CPUMeshes {
+        meshes
+            .flat_map(|mesh| {
+                mesh.primitives()
+                    .filter_map(|primitive| {
+                        self.primitive_to_cpu_mesh(&mesh, &primitive, bake_texture_transforms)
+                            .map(|cpu_mesh| ((mesh.index(), primitive.index()), cpu_mesh))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Converts a single mesh primitive into a `three-d` mesh
+    ///
+    /// Returns `None` if the mesh or primitive does not exist, or if the primitive can not be converted
+    /// (see [`cpu_meshes`](#method.cpu_meshes))
+    pub fn cpu_mesh(&self, mesh: usize, primitive: usize) -> Option<CPUMesh> {
+        let mesh = self.document().meshes().nth(mesh)?;
+        let primitive = mesh.primitives().nth(primitive)?;
+        self.primitive_to_cpu_mesh(&mesh, &primitive, false)
+    }
+
+    fn primitive_to_cpu_mesh(
+        &self,
+        mesh: &Mesh,
+        primitive: &Primitive,
+        bake_texture_transforms: bool,
+    ) -> Option<CPUMesh> {
+        let buffers = self.buffers();
+        let reader = primitive.reader(|buffer| buffers.get(&buffer.index()).map(|data| &data[..]));
+
+        let positions: Vec<f32> = reader.read_positions()?.flatten().collect();
+        let vertex_count = positions.len() / 3;
+
+        let indices = reader
+            .read_indices()
+            .map(|indices| indices.into_u32().collect::<Vec<_>>());
+        let indices = match primitive.mode() {
+            Mode::Triangles => indices,
+            Mode::TriangleStrip => Some(triangle_strip_to_list(
+                &indices.unwrap_or_else(|| (0..vertex_count as u32).collect()),
+            )),
+            Mode::TriangleFan => Some(triangle_fan_to_list(
+                &indices.unwrap_or_else(|| (0..vertex_count as u32).collect()),
+            )),
+            _ => return None,
+        };
+
+        let texture_transform = if bake_texture_transforms {
+            self.common_texture_transform(&primitive.material())
+        } else {
+            None
+        };
+
+        Some(CPUMesh {
+            name: match mesh.name() {
+                Some(name) => format!("{}_{}", name, primitive.index()),
+                None => format!("mesh_{}_{}", mesh.index(), primitive.index()),
+            },
+            material_name: material_name(&primitive.material()),
+            positions,
+            indices,
+            normals: reader
+                .read_normals()
+                .map(|normals| normals.flatten().collect()),
+            uvs: reader
+                .read_tex_coords(0)
+                .map(|uvs| match texture_transform {
+                    Some(transform) => uvs.into_f32().flat_map(|uv| transform.apply(uv)).collect(),
+                    None => uvs.into_f32().flatten().collect(),
+                }),
+            ..Default::default()
+        })
+    }
+
+    /// The texture transformation shared by all texture slots of `material`, if it can be baked into the first set
+    /// of texture coordinates
+    fn common_texture_transform(&self, material: &Material) -> Option<TextureTransform> {
+        let mut transforms = texture_slots(material)
+            .into_iter()
+            .map(|(slot, tex_coord)| {
+                let transform = self.texture_transform(material, slot).unwrap_or_default();
+                let tex_coord = transform.tex_coord.unwrap_or(tex_coord);
+                (
+                    tex_coord,
+                    TextureTransform {
+                        tex_coord: None,
+                        ..transform
+                    },
+                )
+            });
+
+        let first = transforms.next()?;
+        if first.0 == 0 && !first.1.is_identity() && transforms.all(|other| other == first) {
+            Some(first.1)
+        } else {
+            None
+        }
+    }
+}
+
+/// Checks that all accessors can be read from the loaded buffers
+///
+/// `gltf` neither checks the ranges of accessors and buffer views against the buffer data nor the types of the mesh
+/// attributes, its reader panics on such documents. Hostile or truncated files are thus rejected during the import.
+pub(crate) fn validate_accessors(document: &Document, buffers: &LoadedBuffers) -> Result<()> {
+    for view in document.views() {
+        let buffer = view.buffer().index();
+        let actual = buffers.get(&buffer).map_or(0, |data| data.len());
+        match view.offset().checked_add(view.length()) {
+            Some(end) if end <= actual => {}
+            end => {
+                let kind = ResourceErrorKind::BufferLength {
+                    expected: end.unwrap_or(usize::MAX),
+                    actual,
+                };
+                return Err(ResourceError::new(ImportPhase::Buffers, buffer, kind).into());
+            }
+        }
+    }
+
+    for accessor in document.accessors() {
+        let invalid = |field| invalid_accessor(&accessor, field);
+        if let Some(view) = accessor.view() {
+            if !fits_into_view(&view, accessor.offset(), accessor.count(), accessor.size()) {
+                return Err(invalid("count"));
+            }
+        }
+        if let Some(sparse) = accessor.sparse() {
+            let count = sparse.count() as usize;
+            let indices = sparse.indices();
+            let index_size = indices.index_type().size();
+            if !fits_into_view(
+                &indices.view(),
+                indices.offset() as usize,
+                count,
+                index_size,
+            ) {
+                return Err(invalid("sparse"));
+            }
+            let values = sparse.values();
+            if !fits_into_view(
+                &values.view(),
+                values.offset() as usize,
+                count,
+                accessor.size(),
+            ) {
+                return Err(invalid("sparse"));
+            }
+        }
+    }
+
+    for mesh in document.meshes() {
+        for primitive in mesh.primitives() {
+            for (semantic, accessor) in primitive.attributes() {
+                let valid = match semantic {
+                    Semantic::Positions | Semantic::Normals => {
+                        accessor.data_type() == DataType::F32
+                            && accessor.dimensions() == Dimensions::Vec3
+                    }
+                    Semantic::TexCoords(_) => {
+                        matches!(
+                            accessor.data_type(),
+                            DataType::U8 | DataType::U16 | DataType::F32
+                        ) && accessor.dimensions() == Dimensions::Vec2
+                    }
+                    _ => true,
+                };
+                if !valid {
+                    return Err(invalid_accessor(&accessor, "type"));
+                }
+            }
+            if let Some(indices) = primitive.indices() {
+                let valid = matches!(
+                    indices.data_type(),
+                    DataType::U8 | DataType::U16 | DataType::U32
+                ) && indices.dimensions() == Dimensions::Scalar;
+                if !valid {
+                    return Err(invalid_accessor(&indices, "type"));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether `count` elements of `size` bytes starting at `offset` are within the buffer view
+fn fits_into_view(view: &View, offset: usize, count: usize, size: usize) -> bool {
+    let stride = view.stride().unwrap_or(size);
+    if count == 0 || stride < size {
+        return false;
+    }
+
+    let end = stride
+        .checked_mul(count - 1)
+        .and_then(|length| length.checked_add(size))
+        .and_then(|length| length.checked_add(offset));
+    matches!(end, Some(end) if end <= view.length())
+}
+
+fn invalid_accessor(accessor: &Accessor, field: &str) -> ImportError {
+    let path = Path::new()
+        .field("accessors")
+        .index(accessor.index())
+        .field(field);
+    Error::Validation(vec![(path, validation::Error::Invalid)]).into()
+}
+
+fn triangle_strip_to_list(strip: &[u32]) -> Vec<u32> {
+    let mut list = Vec::with_capacity(strip.len().saturating_sub(2) * 3);
+    for (i, triangle) in strip.windows(3).enumerate() {
+        // Every second triangle of a strip has reversed winding order
+        if i % 2 == 0 {
+            list.extend_from_slice(&[triangle[0], triangle[1], triangle[2]]);
+        } else {
+            list.extend_from_slice(&[triangle[1], triangle[0], triangle[2]]);
+        }
+    }
+    list
+}
+
+fn triangle_fan_to_list(fan: &[u32]) -> Vec<u32> {
+    let mut list = Vec::with_capacity(fan.len().saturating_sub(2) * 3);
+    if let Some((&center, rest)) = fan.split_first() {
+        for edge in rest.windows(2) {
+            list.extend_from_slice(&[center, edge[0], edge[1]]);
+        }
+    }
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::GltfImporter;
+    use crate::test_util::PNG_DATA_URI;
+    use crate::texture::TextureSlot;
+    use futures_executor::block_on;
+    use gltf::Gltf;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_convert_triangle_model() {
+        let base = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Triangle/glTF"
+        ));
+        let gltf = Gltf::open(base.join("Triangle.gltf")).unwrap();
+        GltfImporter::import(gltf, Some(base), |imported| {
+            let meshes = imported.unwrap().cpu_meshes();
+            assert_eq!(meshes.len(), 1);
+
+            let triangle = &meshes[&(0, 0)];
+            assert_eq!(triangle.positions.len(), 9);
+            assert_eq!(triangle.indices, Some(vec![0, 1, 2]));
+            assert_eq!(triangle.normals, None);
+            assert_eq!(triangle.uvs, None);
+        });
+    }
+
+    #[test]
+    fn test_convert_cube_model() {
+        let base = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Cube/glTF"
+        ));
+        let gltf = Gltf::open(base.join("Cube.gltf")).unwrap();
+        GltfImporter::import(gltf, Some(base), |imported| {
+            let result = imported.unwrap();
+            let meshes = result.cpu_meshes();
+            assert_eq!(meshes.len(), 1);
+
+            let cube = result.cpu_mesh(0, 0).unwrap();
+            let vertex_count = cube.positions.len() / 3;
+            assert_eq!(
+                cube.normals.map(|normals| normals.len() / 3),
+                Some(vertex_count)
+            );
+            assert_eq!(cube.uvs.map(|uvs| uvs.len() / 2), Some(vertex_count));
+            assert_eq!(cube.indices.map(|indices| indices.len() % 3), Some(0));
+        });
+    }
+
+    /// Document with a triangle whose positions are read from a buffer view exceeding the (truncated) buffer
+    const TRUNCATED_BUFFER_DOCUMENT: &str = r#"{
+        "asset": { "version": "2.0" },
+        "buffers": [{ "byteLength": 12, "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAA" }],
+        "bufferViews": [{ "buffer": 0, "byteLength": 36 }],
+        "accessors": [
+            { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0] }
+        ],
+        "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0 } }] }]
+    }"#;
+
+    #[test]
+    fn test_import_truncated_buffer() {
+        let gltf = Gltf::from_slice(TRUNCATED_BUFFER_DOCUMENT.as_bytes()).unwrap();
+        GltfImporter::import(gltf, None, |imported| match imported {
+            Err(ImportError::Resource(error)) => {
+                assert_eq!(error.phase, ImportPhase::Buffers);
+                assert_eq!(error.index, 0);
+                assert!(matches!(
+                    error.kind,
+                    ResourceErrorKind::BufferLength { expected: 36, actual } if actual < 36
+                ));
+            }
+            _ => panic!("truncated buffer has been imported"),
+        });
+
+        // The buffer view fits into the buffer, but the accessor exceeds the buffer view
+        let document =
+            TRUNCATED_BUFFER_DOCUMENT.replace(r#""byteLength": 36"#, r#""byteLength": 12"#);
+        let gltf = Gltf::from_slice(document.as_bytes()).unwrap();
+        GltfImporter::import(gltf, None, |imported| {
+            assert!(matches!(
+                imported,
+                Err(ImportError::Document(Error::Validation(_)))
+            ));
+        });
+    }
+
+    /// Document with two materials transforming the base color texture, for a mesh with two primitives
+    fn texture_transform_document() -> String {
+        format!(
+            r#"{{
+                "asset": {{ "version": "2.0" }},
+                "buffers": [{{
+                    "byteLength": 60,
+                    "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAAAAAAAAAAAAAAAIA/AAAAAAAAAAAAAIA/"
+                }}],
+                "bufferViews": [{{ "buffer": 0, "byteLength": 36 }}, {{ "buffer": 0, "byteOffset": 36, "byteLength": 24 }}],
+                "accessors": [
+                    {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0] }},
+                    {{ "bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC2" }}
+                ],
+                "meshes": [{{
+                    "primitives": [
+                        {{ "attributes": {{ "POSITION": 0, "TEXCOORD_0": 1 }}, "material": 0 }},
+                        {{ "attributes": {{ "POSITION": 0, "TEXCOORD_0": 1 }}, "material": 1 }}
+                    ]
+                }}],
+                "materials": [
+                    {{
+                        "pbrMetallicRoughness": {{
+                            "baseColorTexture": {{
+                                "index": 0,
+                                "extensions": {{ "KHR_texture_transform": {{ "offset": [0.5, 0], "scale": [2, 2] }} }}
+                            }}
+                        }}
+                    }},
+                    {{
+                        "pbrMetallicRoughness": {{
+                            "baseColorTexture": {{
+                                "index": 0,
+                                "extensions": {{ "KHR_texture_transform": {{ "offset": [0.5, 0] }} }}
+                            }}
+                        }},
+                        "emissiveTexture": {{ "index": 0 }}
+                    }}
+                ],
+                "textures": [{{ "source": 0 }}],
+                "images": [
+                    {{ "uri": "{png}" }}
+                ]
+            }}"#,
+            png = PNG_DATA_URI
+        )
+    }
+
+    #[test]
+    fn test_bake_texture_transforms() {
+        GltfImporter::import_from_slice(
+            texture_transform_document().as_bytes(),
+            None,
+            |imported| {
+                let result = imported.unwrap();
+                let material = result.material(0).unwrap();
+                assert_eq!(
+                    material.texture_transforms[&TextureSlot::BaseColor].offset,
+                    [0.5, 0.0]
+                );
+                let material = result.material(1).unwrap();
+                assert!(!material
+                    .texture_transforms
+                    .contains_key(&TextureSlot::Emissive));
+
+                let uvs = vec![0.0, 0.0, 1.0, 0.0, 0.0, 1.0];
+                assert_eq!(result.cpu_mesh(0, 0).unwrap().uvs, Some(uvs.clone()));
+
+                let meshes = result.cpu_meshes_with_texture_transforms();
+                assert_eq!(
+                    meshes[&(0, 0)].uvs,
+                    Some(vec![0.5, 0.0, 2.5, 0.0, 0.5, 2.0])
+                );
+                // The emissive texture is not transformed, so nothing is baked
+                assert_eq!(meshes[&(0, 1)].uvs, Some(uvs));
+            },
+        );
+    }
+
+    #[test]
+    fn test_texture_transforms_need_raw_document() {
+        let gltf = Gltf::from_slice(texture_transform_document().as_bytes()).unwrap();
+        GltfImporter::import(gltf, None, |imported| {
+            let result = imported.unwrap();
+            assert!(result.material(0).unwrap().texture_transforms.is_empty());
+            assert_eq!(
+                result.cpu_meshes_with_texture_transforms()[&(0, 0)].uvs,
+                Some(vec![0.0, 0.0, 1.0, 0.0, 0.0, 1.0])
+            );
+        });
+
+        let result = block_on(GltfImporter::import_from_slice_async(
+            texture_transform_document().as_bytes(),
+            None,
+        ))
+        .unwrap();
+        assert_eq!(
+            result.cpu_meshes_with_texture_transforms()[&(0, 0)].uvs,
+            Some(vec![0.5, 0.0, 2.5, 0.0, 0.5, 2.0])
+        );
+    }
+
+    #[test]
+    fn test_triangle_strip_and_fan_to_list() {
+        assert_eq!(
+            triangle_strip_to_list(&[0, 1, 2, 3, 4]),
+            vec![0, 1, 2, 2, 1, 3, 2, 3, 4]
+        );
+        assert_eq!(
+            triangle_fan_to_list(&[0, 1, 2, 3, 4]),
+            vec![0, 1, 2, 0, 2, 3, 0, 3, 4]
+        );
+    }
+}
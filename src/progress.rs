@@ -0,0 +1,260 @@
+use gltf::Document;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Callback receiving progress updates during an import
+pub type ProgressCallback = Rc<dyn Fn(&ImportProgress)>;
+
+/// A buffer or image that finished loading
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FinishedResource {
+    /// A buffer finished loading
+    Buffer {
+        /// Index from the `buffers` section of the GLTF document
+        index: usize,
+        /// Size of the buffer data in bytes
+        bytes: usize,
+    },
+
+    /// An image finished loading and decoding
+    ///
+    /// Also reported for images that failed if the import is lenient about failing images, with 0 `bytes`
+    Image {
+        /// Index from the `images` section of the GLTF document
+        index: usize,
+        /// Size of the encoded image data in bytes
+        bytes: usize,
+    },
+}
+
+/// Progress of an import
+///
+/// Reported to the callback set via
+/// [`ImportOptions::with_progress`](../import/struct.ImportOptions.html#method.with_progress)
+/// once when the import starts, while the [resolver](../resolver/trait.ResourceResolver.html) loads external
+/// resources and every time a buffer or image finished loading.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ImportProgress {
+    /// Number of buffers in the document
+    pub total_buffers: usize,
+    /// Number of buffers that finished loading
+    pub finished_buffers: usize,
+    /// Sum of the buffer sizes declared in the document, in bytes
+    pub total_buffer_bytes: usize,
+    /// Number of bytes of buffers that finished loading
+    pub finished_buffer_bytes: usize,
+    /// Number of images in the document
+    pub total_images: usize,
+    /// Number of images that finished loading and decoding
+    pub finished_images: usize,
+    /// Number of (encoded) bytes of images that finished loading
+    pub finished_image_bytes: usize,
+    /// Number of external resources requested from the resolver so far
+    pub requested_resources: usize,
+    /// Number of requested external resources that have been loaded by the resolver, before decoding
+    pub loaded_resources: usize,
+    /// The resource that finished loading and caused this update, `None` for the initial update and for updates
+    /// of `loaded_resources`
+    pub last_finished: Option<FinishedResource>,
+}
+
+impl ImportProgress {
+    /// Number of resources (buffers and images) in the document
+    pub fn total(&self) -> usize {
+        self.total_buffers + self.total_images
+    }
+
+    /// Number of resources (buffers and images) that finished loading
+    pub fn finished(&self) -> usize {
+        self.finished_buffers + self.finished_images
+    }
+
+    /// Whether all resources finished loading
+    pub fn is_done(&self) -> bool {
+        self.finished() == self.total()
+    }
+}
+
+/// Keeps track of the progress of a single import and reports it to the callback
+pub(crate) struct ProgressReporter {
+    callback: Option<ProgressCallback>,
+    progress: RefCell<ImportProgress>,
+}
+
+impl ProgressReporter {
+    pub(crate) fn new(callback: Option<ProgressCallback>, document: &Document) -> Rc<Self> {
+        let reporter = Rc::new(Self {
+            callback,
+            progress: RefCell::new(ImportProgress {
+                total_buffers: document.buffers().len(),
+                // Declared lengths may overflow a `usize` on 32-bit targets
+                total_buffer_bytes: document
+                    .buffers()
+                    .fold(0, |sum, buffer| sum.saturating_add(buffer.length())),
+                total_images: document.images().len(),
+                ..Default::default()
+            }),
+        });
+        reporter.report();
+        reporter
+    }
+
+    pub(crate) fn finished(&self, resource: FinishedResource) {
+        {
+            let mut progress = self.progress.borrow_mut();
+            match resource {
+                FinishedResource::Buffer { bytes, .. } => {
+                    progress.finished_buffers += 1;
+                    progress.finished_buffer_bytes += bytes;
+                }
+                FinishedResource::Image { bytes, .. } => {
+                    progress.finished_images += 1;
+                    progress.finished_image_bytes += bytes;
+                }
+            }
+            progress.last_finished = Some(resource);
+        }
+        self.report();
+    }
+
+    /// Registers a batch of `count` resources requested from the resolver, returning the callback for its
+    /// [`LoadOptions`](../resolver/struct.LoadOptions.html)
+    pub(crate) fn loading(self: &Rc<Self>, count: usize) -> impl Fn(f32) {
+        let loaded_before = {
+            let mut progress = self.progress.borrow_mut();
+            progress.requested_resources += count;
+            progress.loaded_resources
+        };
+
+        let reporter = self.clone();
+        move |fraction| {
+            let loaded = loaded_before + (fraction.clamp(0.0, 1.0) * count as f32).round() as usize;
+            {
+                let mut progress = reporter.progress.borrow_mut();
+                if loaded <= progress.loaded_resources {
+                    return;
+                }
+                progress.loaded_resources = loaded;
+                progress.last_finished = None;
+            }
+            reporter.report();
+        }
+    }
+
+    /// Marks all requested resources as loaded, for resolvers that do not report their progress
+    pub(crate) fn loading_done(&self) {
+        let mut progress = self.progress.borrow_mut();
+        progress.loaded_resources = progress.requested_resources;
+    }
+
+    fn report(&self) {
+        if let Some(callback) = &self.callback {
+            callback(&self.progress.borrow());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::{GltfImporter, ImportOptions};
+    use crate::resolver::{LoadOptions, LoadedResources, Resource, ResourceResolver};
+    use gltf::Gltf;
+    use std::path::PathBuf;
+
+    /// Resolver returning 4 zero bytes for every resource, reporting its progress halfway through
+    struct HalfwayResolver;
+
+    impl ResourceResolver for HalfwayResolver {
+        fn load(&self, resources: Vec<Resource>, on_done: Box<dyn FnOnce(LoadedResources)>) {
+            let loaded = resources
+                .into_iter()
+                .map(|resource| (resource, Ok(vec![0; 4].into())))
+                .collect();
+            on_done(loaded)
+        }
+
+        fn load_with_options(
+            &self,
+            resources: Vec<Resource>,
+            options: LoadOptions,
+            on_done: Box<dyn FnOnce(LoadedResources)>,
+        ) {
+            options.report_progress(0.5);
+            options.report_progress(1.0);
+            self.load(resources, on_done)
+        }
+    }
+
+    #[test]
+    fn test_progress_cube_model() {
+        let base = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Cube/glTF"
+        ));
+        let gltf = Gltf::open(base.join("Cube.gltf")).unwrap();
+        let updates = Rc::new(RefCell::new(Vec::new()));
+        let options = ImportOptions::new().with_progress({
+            let updates = updates.clone();
+            move |progress: &ImportProgress| updates.borrow_mut().push(progress.clone())
+        });
+        GltfImporter::import_with_options(gltf, Some(base), options, |imported| {
+            assert!(imported.is_ok());
+        });
+
+        let updates = updates.borrow();
+        assert_eq!(
+            updates
+                .iter()
+                .filter(|progress| progress.last_finished.is_some())
+                .count(),
+            3
+        );
+        assert_eq!(updates[0].last_finished, None);
+        assert_eq!(updates[0].total(), 3);
+        assert_eq!(updates[0].finished(), 0);
+
+        let last = updates.last().unwrap();
+        assert!(last.is_done());
+        assert_eq!(last.finished_buffer_bytes, last.total_buffer_bytes);
+        assert_eq!(last.loaded_resources, 3);
+        assert!(last.finished_image_bytes > 0);
+        assert!(matches!(
+            last.last_finished,
+            Some(FinishedResource::Image { index: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_progress_within_resolver_batch() {
+        let gltf = Gltf::from_slice(
+            br#"{
+                "asset": { "version": "2.0" },
+                "buffers": [{ "byteLength": 4, "uri": "a.bin" }, { "byteLength": 4, "uri": "b.bin" }]
+            }"#,
+        )
+        .unwrap();
+        let updates = Rc::new(RefCell::new(Vec::new()));
+        let options = ImportOptions::new()
+            .with_resolver(HalfwayResolver)
+            .with_progress({
+                let updates = updates.clone();
+                move |progress: &ImportProgress| updates.borrow_mut().push(progress.clone())
+            });
+        GltfImporter::import_with_options(gltf, Some(PathBuf::new()), options, |imported| {
+            assert!(imported.is_ok());
+        });
+
+        let updates = updates.borrow();
+        let loaded: Vec<_> = updates
+            .iter()
+            .map(|progress| (progress.loaded_resources, progress.finished_buffers))
+            .collect();
+        assert_eq!(loaded, vec![(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)]);
+        assert!(updates[1..]
+            .iter()
+            .all(|progress| progress.requested_resources == 2));
+        assert_eq!(updates[2].last_finished, None);
+    }
+}
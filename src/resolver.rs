@@ -0,0 +1,582 @@
+use std::collections::HashMap;
+use std::io;
+use std::ops::{Deref, Range};
+#[cfg(all(feature = "mmap", not(target_arch = "wasm32")))]
+use std::path::Path;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+use three_d::{IOError, Loader};
+
+/// Loaded resource data
+///
+/// Keys of the hashmap are the resources that have been requested from the resolver
+pub type LoadedResources = HashMap<Resource, io::Result<ResourceData>>;
+
+/// Data of a loaded resource
+///
+/// Buffers are imported without copying their data, so resolvers can hand over data they share with others
+/// (e.g. an asset pack kept in memory) or memory-mapped files.
+#[derive(Debug)]
+pub enum ResourceData {
+    /// Data owned by the import
+    Owned(Vec<u8>),
+    /// Data shared with the resolver
+    Shared(Arc<[u8]>),
+    /// A memory-mapped file, see [`MmapResolver`](struct.MmapResolver.html)
+    #[cfg(all(feature = "mmap", not(target_arch = "wasm32")))]
+    Mapped(memmap2::Mmap),
+    /// A range of other data, e.g. the binary chunk of a loaded `.glb` file, or a resource loaded once for several
+    /// imports running at the same time
+    Slice(Rc<ResourceData>, Range<usize>),
+}
+
+impl Deref for ResourceData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            ResourceData::Owned(data) => data,
+            ResourceData::Shared(data) => data,
+            #[cfg(all(feature = "mmap", not(target_arch = "wasm32")))]
+            ResourceData::Mapped(data) => data,
+            ResourceData::Slice(data, range) => &data[range.clone()],
+        }
+    }
+}
+
+impl AsRef<[u8]> for ResourceData {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl From<Vec<u8>> for ResourceData {
+    fn from(data: Vec<u8>) -> Self {
+        ResourceData::Owned(data)
+    }
+}
+
+impl From<Arc<[u8]>> for ResourceData {
+    fn from(data: Arc<[u8]>) -> Self {
+        ResourceData::Shared(data)
+    }
+}
+
+#[cfg(all(feature = "mmap", not(target_arch = "wasm32")))]
+impl From<memmap2::Mmap> for ResourceData {
+    fn from(data: memmap2::Mmap) -> Self {
+        ResourceData::Mapped(data)
+    }
+}
+
+/// An external resource (buffer or image) referenced by a GLTF document
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Resource {
+    /// A path relative to the base path of the document, e.g. `textures/wood.png`
+    Relative {
+        /// The base path given to the importer
+        base: PathBuf,
+        /// The (relative) URI from the document, percent-decoded and without query or fragment
+        uri: String,
+    },
+
+    /// An absolute file path from a `file:` URI
+    File(PathBuf),
+
+    /// An external `http[s]://` URL
+    External(String),
+}
+
+impl Resource {
+    /// The path this resource would be loaded from by [`three-d`'s Loader](https://docs.rs/three-d/latest/three_d/io/struct.Loader.html)
+    pub fn path(&self) -> PathBuf {
+        match self {
+            Resource::Relative { base, uri } => base.join(uri),
+            Resource::File(path) => path.clone(),
+            Resource::External(url) => PathBuf::from(url),
+        }
+    }
+}
+
+/// Options for loading a batch of resources, see [`ResourceResolver::load_with_options`](trait.ResourceResolver.html#method.load_with_options)
+#[derive(Clone, Default)]
+pub struct LoadOptions {
+    progress: Option<Rc<dyn Fn(f32)>>,
+    max_bytes: Option<usize>,
+}
+
+impl LoadOptions {
+    /// Options without a progress callback or size limit
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a callback receiving the fraction (0.0 to 1.0) of resources of the batch that finished loading
+    pub fn with_progress<F: 'static + Fn(f32)>(mut self, callback: F) -> Self {
+        self.progress = Some(Rc::new(callback));
+        self
+    }
+
+    /// Limits the size of each resource of the batch in bytes
+    pub fn with_max_bytes(mut self, max: usize) -> Self {
+        self.max_bytes = Some(max);
+        self
+    }
+
+    /// Reports the fraction (0.0 to 1.0) of resources of the batch that finished loading
+    pub fn report_progress(&self, fraction: f32) {
+        if let Some(progress) = &self.progress {
+            progress(fraction);
+        }
+    }
+
+    /// Maximum size of each resource of the batch in bytes
+    ///
+    /// Resolvers that know the size of a resource before loading it should fail larger resources with
+    /// `io::ErrorKind::InvalidData` instead of loading them. The importer checks the loaded data regardless.
+    pub fn max_bytes(&self) -> Option<usize> {
+        self.max_bytes
+    }
+}
+
+/// Loads the external resources referenced by a GLTF document
+///
+/// Implement this trait to load buffers and images from other sources than the file system or network,
+/// e.g. from asset packs or in-memory file systems, and set it via
+/// [`ImportOptions::with_resolver`](../import/struct.ImportOptions.html#method.with_resolver).
+///
+/// ```rust
+/// use std::io;
+/// use three_d_gltf_import::resolver::{LoadedResources, Resource, ResourceResolver};
+///
+/// struct InMemoryResolver {
+///     files: std::collections::HashMap<std::path::PathBuf, Vec<u8>>,
+/// }
+///
+/// impl ResourceResolver for InMemoryResolver {
+///     fn load(&self, resources: Vec<Resource>, on_done: Box<dyn FnOnce(LoadedResources)>) {
+///         let loaded = resources
+///             .into_iter()
+///             .map(|resource| {
+///                 let data = self
+///                     .files
+///                     .get(&resource.path())
+///                     .map(|data| data.clone().into())
+///                     .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound));
+///                 (resource, data)
+///             })
+///             .collect();
+///         on_done(loaded)
+///     }
+/// }
+/// ```
+pub trait ResourceResolver {
+    /// Loads all `resources` and calls `on_done` with the result for each of them once done
+    ///
+    /// Loading may happen synchronously or asynchronously, similar to `three-d`'s Loader. `on_done` must be called
+    /// exactly once, async imports fail with [`ImportError::Aborted`](../error/enum.ImportError.html#variant.Aborted)
+    /// if it is dropped without being called.
+    fn load(&self, resources: Vec<Resource>, on_done: Box<dyn FnOnce(LoadedResources)>);
+
+    /// Loads all `resources` like [`load`](#tymethod.load), reporting the progress within the batch via
+    /// [`LoadOptions::report_progress`](struct.LoadOptions.html#method.report_progress)
+    ///
+    /// The default implementation ignores `options` and calls `load`, thus only reporting progress once the
+    /// whole batch is done.
+    fn load_with_options(
+        &self,
+        resources: Vec<Resource>,
+        options: LoadOptions,
+        on_done: Box<dyn FnOnce(LoadedResources)>,
+    ) {
+        let _ = options;
+        self.load(resources, on_done)
+    }
+}
+
+/// The default resolver, loading resources via [`three-d`'s Loader](https://docs.rs/three-d/latest/three_d/io/struct.Loader.html)
+///
+/// The Loader keeps the loaded data, so it is copied once for the import.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LoaderResolver;
+
+impl ResourceResolver for LoaderResolver {
+    fn load(&self, resources: Vec<Resource>, on_done: Box<dyn FnOnce(LoadedResources)>) {
+        self.load_with_options(resources, LoadOptions::new(), on_done)
+    }
+
+    fn load_with_options(
+        &self,
+        resources: Vec<Resource>,
+        options: LoadOptions,
+        on_done: Box<dyn FnOnce(LoadedResources)>,
+    ) {
+        let total = resources.len();
+        let mut result = LoadedResources::with_capacity(total);
+        let mut to_load = Vec::with_capacity(total);
+        for resource in resources {
+            match check_size(&resource, options.max_bytes()) {
+                Ok(()) => to_load.push(resource),
+                Err(err) => {
+                    result.insert(resource, Err(err));
+                }
+            }
+        }
+        if to_load.is_empty() {
+            options.report_progress(1.0);
+            return on_done(result);
+        }
+        let paths: Vec<_> = to_load.iter().map(Resource::path).collect();
+
+        // Rejected resources count as done
+        let rejected = result.len() as f32;
+        let progress = move |fraction: f32| {
+            options
+                .report_progress((rejected + fraction * (total as f32 - rejected)) / total as f32)
+        };
+        Loader::load_with_progress(paths.clone().as_slice(), progress, move |loaded| {
+            result.extend(to_load.into_iter().zip(paths).map(|(resource, path)| {
+                let data = match loaded.bytes(path) {
+                    Ok(bytes) => Ok(ResourceData::Owned(bytes.to_owned())),
+                    Err(err) => Err(io_error(err)),
+                };
+                (resource, data)
+            }));
+
+            on_done(result);
+        });
+    }
+}
+
+/// Fails local files larger than `max_bytes` without reading them
+#[cfg(not(target_arch = "wasm32"))]
+fn check_size(resource: &Resource, max_bytes: Option<usize>) -> io::Result<()> {
+    let max = match (resource, max_bytes) {
+        (Resource::External(_), _) | (_, None) => return Ok(()),
+        (_, Some(max)) => max,
+    };
+
+    // Missing files are reported by the loader
+    match std::fs::metadata(resource.path()) {
+        Ok(metadata) if metadata.len() > max as u64 => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} bytes exceed the limit of {} bytes", metadata.len(), max),
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn check_size(_resource: &Resource, _max_bytes: Option<usize>) -> io::Result<()> {
+    Ok(())
+}
+
+/// Resolver memory-mapping files instead of reading them, so large buffers are neither copied nor read upfront
+///
+/// Only available on native targets with the `mmap` feature. `http[s]://` URLs are loaded via
+/// [`LoaderResolver`](struct.LoaderResolver.html).
+///
+/// The mapped files must not be modified (or truncated) while they are mapped, i.e. as long as the import or the
+/// imported model (and any [`ImportCache`](../cache/struct.ImportCache.html) holding its buffers) is alive.
+/// Otherwise the data of the model changes, or accessing it crashes the process.
+///
+/// ```rust
+/// use three_d_gltf_import::import::ImportOptions;
+/// use three_d_gltf_import::resolver::MmapResolver;
+///
+/// let options = ImportOptions::new().with_resolver(MmapResolver);
+/// ```
+#[cfg(all(feature = "mmap", not(target_arch = "wasm32")))]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MmapResolver;
+
+#[cfg(all(feature = "mmap", not(target_arch = "wasm32")))]
+impl ResourceResolver for MmapResolver {
+    fn load(&self, resources: Vec<Resource>, on_done: Box<dyn FnOnce(LoadedResources)>) {
+        self.load_with_options(resources, LoadOptions::new(), on_done)
+    }
+
+    fn load_with_options(
+        &self,
+        resources: Vec<Resource>,
+        options: LoadOptions,
+        on_done: Box<dyn FnOnce(LoadedResources)>,
+    ) {
+        let (external, files): (Vec<_>, Vec<_>) = resources
+            .into_iter()
+            .partition(|resource| matches!(resource, Resource::External(_)));
+        let mut loaded: LoadedResources = files
+            .into_iter()
+            .map(|resource| {
+                let data = check_size(&resource, options.max_bytes())
+                    .and_then(|()| map_file(&resource.path()));
+                (resource, data)
+            })
+            .collect();
+
+        if external.is_empty() {
+            options.report_progress(1.0);
+            return on_done(loaded);
+        }
+        LoaderResolver.load_with_options(
+            external,
+            options,
+            Box::new(move |external| {
+                loaded.extend(external);
+                on_done(loaded)
+            }),
+        );
+    }
+}
+
+#[cfg(all(feature = "mmap", not(target_arch = "wasm32")))]
+fn map_file(path: &Path) -> io::Result<ResourceData> {
+    let file = std::fs::File::open(path)?;
+    // Safety: the file must not be modified while mapped, which is documented on `MmapResolver`
+    let map = unsafe { memmap2::Mmap::map(&file)? };
+    Ok(ResourceData::Mapped(map))
+}
+
+fn io_error(err: IOError) -> io::Error {
+    // `Loaded::bytes` only reports missing resources as `FailedToLoad`, the underlying error kind is lost there
+    match err {
+        IOError::FailedToLoad { message } => io::Error::other(message),
+        err => io::Error::other(format!("{:?}", err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::{GltfImporter, ImportOptions};
+    use gltf::Gltf;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Resolver reading files directly, recording all requested resources
+    #[derive(Clone, Default)]
+    struct RecordingResolver {
+        requested: Rc<RefCell<Vec<Resource>>>,
+    }
+
+    impl ResourceResolver for RecordingResolver {
+        fn load(&self, resources: Vec<Resource>, on_done: Box<dyn FnOnce(LoadedResources)>) {
+            self.requested
+                .borrow_mut()
+                .extend(resources.iter().cloned());
+            let loaded = resources
+                .into_iter()
+                .map(|resource| {
+                    let data = std::fs::read(resource.path()).map(ResourceData::from);
+                    (resource, data)
+                })
+                .collect();
+            on_done(loaded)
+        }
+    }
+
+    #[test]
+    fn test_loader_resolver_rejects_files_exceeding_max_bytes() {
+        let resource = Resource::File(PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "Cargo.toml"
+        )));
+        let loaded = Rc::new(RefCell::new(None));
+        let progress = Rc::new(RefCell::new(Vec::new()));
+        let options = LoadOptions::new().with_max_bytes(16).with_progress({
+            let progress = progress.clone();
+            move |fraction| progress.borrow_mut().push(fraction)
+        });
+        LoaderResolver.load_with_options(
+            vec![resource.clone()],
+            options,
+            Box::new({
+                let loaded = loaded.clone();
+                move |result| *loaded.borrow_mut() = Some(result)
+            }),
+        );
+
+        let loaded = loaded.borrow_mut().take().unwrap();
+        let error = loaded[&resource].as_ref().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(*progress.borrow(), vec![1.0]);
+    }
+
+    #[test]
+    fn test_loader_resolver_reports_progress_of_empty_batch() {
+        let progress = Rc::new(RefCell::new(Vec::new()));
+        let options = LoadOptions::new().with_progress({
+            let progress = progress.clone();
+            move |fraction| progress.borrow_mut().push(fraction)
+        });
+        let loaded = Rc::new(RefCell::new(None));
+        LoaderResolver.load_with_options(
+            Vec::new(),
+            options,
+            Box::new({
+                let loaded = loaded.clone();
+                move |result| *loaded.borrow_mut() = Some(result)
+            }),
+        );
+
+        assert!(loaded.borrow().as_ref().unwrap().is_empty());
+        assert_eq!(*progress.borrow(), vec![1.0]);
+    }
+
+    #[test]
+    fn test_import_cube_model_with_custom_resolver() {
+        let base = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Cube/glTF"
+        ));
+        let gltf = Gltf::open(base.join("Cube.gltf")).unwrap();
+        let resolver = RecordingResolver::default();
+        let options = ImportOptions::new().with_resolver(resolver.clone());
+        GltfImporter::import_with_options(gltf, Some(base.clone()), options, |imported| {
+            let result = imported.unwrap();
+            assert_eq!(result.buffers().len(), 1);
+            assert_eq!(result.images().len(), 2);
+        });
+
+        let requested = resolver.requested.borrow();
+        assert_eq!(requested.len(), 3);
+        assert_eq!(
+            requested[0],
+            Resource::Relative {
+                base,
+                uri: "Cube.bin".to_owned()
+            }
+        );
+    }
+
+    /// Resolver handing out shared data
+    struct SharedResolver {
+        data: Arc<[u8]>,
+    }
+
+    impl ResourceResolver for SharedResolver {
+        fn load(&self, resources: Vec<Resource>, on_done: Box<dyn FnOnce(LoadedResources)>) {
+            let loaded = resources
+                .into_iter()
+                .map(|resource| (resource, Ok(ResourceData::from(self.data.clone()))))
+                .collect();
+            on_done(loaded)
+        }
+    }
+
+    #[test]
+    fn test_import_shared_buffer_without_copying() {
+        let base = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Triangle/glTF"
+        ));
+        let gltf = Gltf::open(base.join("Triangle.gltf")).unwrap();
+        let data: Arc<[u8]> = std::fs::read(base.join("simpleTriangle.bin"))
+            .unwrap()
+            .into();
+        let options = ImportOptions::new().with_resolver(SharedResolver { data: data.clone() });
+        GltfImporter::import_with_options(gltf, Some(base), options, move |imported| {
+            let result = imported.unwrap();
+            assert_eq!(result.buffers()[&0].as_ptr(), data.as_ptr());
+            assert_eq!(result.buffers()[&0].len(), 44);
+            assert_eq!(result.cpu_meshes().len(), 1);
+        });
+    }
+
+    #[cfg(all(feature = "mmap", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_import_cube_model_with_mmap_resolver() {
+        let base = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Cube/glTF"
+        ));
+        let gltf = Gltf::open(base.join("Cube.gltf")).unwrap();
+        let options = ImportOptions::new().with_resolver(MmapResolver);
+        GltfImporter::import_with_options(gltf, Some(base), options, |imported| {
+            let result = imported.unwrap();
+            assert!(matches!(*result.buffers()[&0], ResourceData::Mapped(_)));
+            assert_eq!(result.images().len(), 2);
+        });
+    }
+
+    /// A `.glb` file with a single buffer stored in its binary chunk
+    #[cfg(all(feature = "mmap", not(target_arch = "wasm32")))]
+    fn glb(buffer: &[u8]) -> Vec<u8> {
+        let json = format!(
+            r#"{{"asset":{{"version":"2.0"}},"buffers":[{{"byteLength":{}}}]}}"#,
+            buffer.len()
+        );
+        let chunks = [
+            (json.into_bytes(), b"JSON", b' '),
+            (buffer.to_vec(), b"BIN\0", 0),
+        ];
+        let mut glb = b"glTF".to_vec();
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        glb.extend_from_slice(&0u32.to_le_bytes());
+        for (data, kind, padding) in chunks.iter() {
+            let padded = data.len() + (4 - data.len() % 4) % 4;
+            glb.extend_from_slice(&(padded as u32).to_le_bytes());
+            glb.extend_from_slice(*kind);
+            glb.extend_from_slice(data);
+            glb.resize(glb.len() + padded - data.len(), *padding);
+        }
+        let length = glb.len() as u32;
+        glb[8..12].copy_from_slice(&length.to_le_bytes());
+        glb
+    }
+
+    #[cfg(all(feature = "mmap", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_import_glb_with_mmap_resolver() {
+        let path = std::env::temp_dir().join(format!(
+            "three-d-gltf-import-mmap-{}.glb",
+            std::process::id()
+        ));
+        std::fs::write(&path, glb(&[1, 2, 3, 4, 5])).unwrap();
+
+        let result = Rc::new(RefCell::new(None));
+        let options = ImportOptions::new().with_resolver(MmapResolver);
+        GltfImporter::import_from_path_with_options(&path, options, {
+            let result = result.clone();
+            move |imported| *result.borrow_mut() = Some(imported)
+        });
+        let imported = result.borrow_mut().take().unwrap().unwrap();
+
+        // The binary chunk is a range of the mapped file instead of a copy
+        match &*imported.buffers()[&0] {
+            ResourceData::Slice(data, range) => {
+                assert!(matches!(**data, ResourceData::Mapped(_)));
+                // Including the padding of the chunk
+                assert_eq!(&data[range.clone()], &[1, 2, 3, 4, 5, 0, 0, 0]);
+            }
+            data => panic!("expected a range of the mapped file, got {:?}", data),
+        }
+
+        drop(imported);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_import_fails_for_unresolvable_resource() {
+        let base = PathBuf::from(format!(
+            "{}/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            "sample_models/2.0/Cube/glTF"
+        ));
+        let gltf = Gltf::open(base.join("Cube.gltf")).unwrap();
+        let options = ImportOptions::new().with_resolver(RecordingResolver::default());
+        GltfImporter::import_with_options(
+            gltf,
+            Some(PathBuf::from("does/not/exist")),
+            options,
+            |imported| {
+                assert!(imported.is_err());
+            },
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use crate::resolver::Resource;
+use std::path::{Component, Path, PathBuf};
+
+/// Restricts which resources an import may load
+///
+/// Set via [`ImportOptions::with_sandbox`](../import/struct.ImportOptions.html#method.with_sandbox), e.g. when
+/// importing untrusted (user uploaded) models. Resources that are not allowed fail the import with
+/// [`ResourceErrorKind::Forbidden`](../error/enum.ResourceErrorKind.html#variant.Forbidden).
+///
+/// Paths are checked lexically (resolving `.` and `..` without accessing the file system), symbolic links
+/// within the allowed directories are not detected.
+///
+/// ```rust
+/// use three_d_gltf_import::import::ImportOptions;
+/// use three_d_gltf_import::sandbox::Sandbox;
+///
+/// // Relative paths must stay within the base directory or `shared/textures`, no `file:` or `http[s]://` URIs
+/// let options = ImportOptions::new().with_sandbox(Sandbox::base_dir().allow_dir("shared/textures"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Sandbox {
+    /// Whether paths are restricted to the base and allowed directories
+    confined: bool,
+    /// Directories besides the base directory resources may be loaded from
+    allowed_dirs: Vec<PathBuf>,
+    /// Whether `file:` URIs may be loaded
+    file_uris: bool,
+    /// Whether `http[s]://` URLs may be loaded
+    external: bool,
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Self::unrestricted()
+    }
+}
+
+impl Sandbox {
+    /// Allows loading any resource (the default)
+    pub fn unrestricted() -> Self {
+        Self {
+            confined: false,
+            allowed_dirs: Vec::new(),
+            file_uris: true,
+            external: true,
+        }
+    }
+
+    /// Only allows relative paths that stay within the base directory, `file:` and `http[s]://` URIs are rejected
+    pub fn base_dir() -> Self {
+        Self {
+            confined: true,
+            allowed_dirs: Vec::new(),
+            file_uris: false,
+            external: false,
+        }
+    }
+
+    /// Additionally allows loading resources from `dir` (and its subdirectories)
+    ///
+    /// Only has an effect for sandboxes created via [`base_dir`](#method.base_dir). `dir` is compared with the
+    /// resolved paths as given, so it should be in the same (relative or absolute) form as the base path.
+    pub fn allow_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.allowed_dirs.push(normalize(&dir.into()));
+        self
+    }
+
+    /// Sets whether `file:` URIs may be loaded
+    ///
+    /// If the sandbox is restricted to the base directory, the files also need to be within an
+    /// [allowed directory](#method.allow_dir).
+    pub fn allow_file_uris(mut self, allow: bool) -> Self {
+        self.file_uris = allow;
+        self
+    }
+
+    /// Sets whether `http[s]://` URLs may be loaded
+    pub fn allow_external(mut self, allow: bool) -> Self {
+        self.external = allow;
+        self
+    }
+
+    /// Whether `resource` may be loaded
+    pub fn allows(&self, resource: &Resource) -> bool {
+        match resource {
+            Resource::Relative { base, uri } => {
+                !self.confined || self.is_allowed_path(&base.join(uri), Some(base))
+            }
+            Resource::File(path) => {
+                self.file_uris && (!self.confined || self.is_allowed_path(path, None))
+            }
+            Resource::External(_) => self.external,
+        }
+    }
+
+    fn is_allowed_path(&self, path: &Path, base: Option<&Path>) -> bool {
+        let path = normalize(path);
+        base.map(normalize)
+            .iter()
+            .chain(self.allowed_dirs.iter())
+            .any(|dir| is_within(&path, dir))
+    }
+}
+
+/// Resolves `.` and `..` components of `path` lexically
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                // `..` of the root is the root itself
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => {}
+                _ => normalized.push(".."),
+            },
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+/// Whether the normalized `path` is `dir` or within it
+fn is_within(path: &Path, dir: &Path) -> bool {
+    let parent_dirs = |path: &Path| {
+        path.components()
+            .take_while(|component| *component == Component::ParentDir)
+            .count()
+    };
+
+    // An empty `dir` (i.e. the current directory) is a prefix of any relative path, including `../foo`
+    path.starts_with(dir) && parent_dirs(path) == parent_dirs(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relative(base: &str, uri: &str) -> Resource {
+        Resource::Relative {
+            base: PathBuf::from(base),
+            uri: uri.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_unrestricted_sandbox() {
+        let sandbox = Sandbox::unrestricted();
+        assert!(sandbox.allows(&relative("models", "../../etc/passwd")));
+        assert!(sandbox.allows(&Resource::File(PathBuf::from("/etc/passwd"))));
+        assert!(sandbox.allows(&Resource::External(
+            "https://example.com/wood.png".to_owned()
+        )));
+    }
+
+    #[test]
+    fn test_base_dir_sandbox() {
+        let sandbox = Sandbox::base_dir();
+        assert!(sandbox.allows(&relative("models", "Cube.bin")));
+        assert!(sandbox.allows(&relative("models", "./textures/../Cube.bin")));
+        assert!(sandbox.allows(&relative("/srv/models", "textures/wood.png")));
+        assert!(sandbox.allows(&relative("", "Cube.bin")));
+
+        assert!(!sandbox.allows(&relative("models", "../Cube.bin")));
+        assert!(!sandbox.allows(&relative("models", "textures/../../Cube.bin")));
+        assert!(!sandbox.allows(&relative("models", "/etc/passwd")));
+        assert!(!sandbox.allows(&relative("/srv/models", "../../../etc/passwd")));
+        assert!(!sandbox.allows(&relative("", "../Cube.bin")));
+        assert!(!sandbox.allows(&Resource::File(PathBuf::from("models/Cube.bin"))));
+        assert!(!sandbox.allows(&Resource::External(
+            "https://example.com/wood.png".to_owned()
+        )));
+    }
+
+    #[test]
+    fn test_base_dir_sandbox_with_allowed_dirs() {
+        let sandbox = Sandbox::base_dir()
+            .allow_dir("/srv/shared/")
+            .allow_file_uris(true)
+            .allow_external(true);
+        assert!(sandbox.allows(&relative("/srv/models", "../shared/wood.png")));
+        assert!(sandbox.allows(&Resource::File(PathBuf::from("/srv/shared/wood.png"))));
+        assert!(sandbox.allows(&Resource::External(
+            "https://example.com/wood.png".to_owned()
+        )));
+
+        assert!(!sandbox.allows(&relative("/srv/models", "../other/wood.png")));
+        assert!(!sandbox.allows(&Resource::File(PathBuf::from("/srv/shared/../secret.png"))));
+    }
+}
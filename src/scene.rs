@@ -0,0 +1,264 @@
+use crate::import::ImportedGltfModel;
+use gltf::{Mesh, Node, Scene};
+use std::collections::HashSet;
+use three_d::{InnerSpace, Mat4, SquareMatrix, Vec3};
+
+/// A scene of the GLTF document
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ImportedScene {
+    /// Index of the scene from the `scenes` section of the GLTF document
+    pub index: usize,
+    /// Name of the scene, if any
+    pub name: Option<String>,
+    /// Indexes of the root nodes of the scene
+    pub nodes: Vec<usize>,
+}
+
+impl From<&Scene<'_>> for ImportedScene {
+    fn from(scene: &Scene) -> Self {
+        ImportedScene {
+            index: scene.index(),
+            name: scene.name().map(String::from),
+            nodes: scene.nodes().map(|node| node.index()).collect(),
+        }
+    }
+}
+
+/// An instance of a mesh placed in a scene by a node
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeshInstance {
+    /// Index of the mesh from the `meshes` section of the GLTF document
+    ///
+    /// The converted primitives of the mesh are found by `(mesh, primitive)` in
+    /// [`ImportedGltfModel::cpu_meshes`](../import/struct.ImportedGltfModel.html#method.cpu_meshes).
+    pub mesh: usize,
+    /// Index of the node referencing the mesh
+    pub node: usize,
+    /// Indexes of the nodes from the root node of the scene down to (and including) `node`
+    pub node_path: Vec<usize>,
+    /// Transformation from the mesh to world space, accumulated from the transformations of all nodes in `node_path`
+    pub transform: Mat4,
+}
+
+/// A node of a scene with its path and world transformation
+pub(crate) struct SceneNode<'a> {
+    pub(crate) node: Node<'a>,
+    pub(crate) path: Vec<usize>,
+    pub(crate) transform: Mat4,
+}
+
+impl SceneNode<'_> {
+    /// Origin of the node in world space
+    pub(crate) fn position(&self) -> Vec3 {
+        (self.transform * Vec3::new(0.0, 0.0, 0.0).extend(1.0)).truncate()
+    }
+
+    /// The direction `local` of the node in world space, normalized
+    ///
+    /// `None` if the transformation collapses the direction, i.e. the node (or one of its ancestors) has a zero scale,
+    /// e.g. to hide it in an animation.
+    pub(crate) fn direction(&self, local: Vec3) -> Option<Vec3> {
+        let direction = (self.transform * local.extend(0.0)).truncate();
+        let length = direction.magnitude();
+        if length.is_normal() {
+            Some(direction / length)
+        } else {
+            None
+        }
+    }
+}
+
+impl ImportedGltfModel {
+    /// All scenes of the document, in the order of the `scenes` section
+    pub fn scenes(&self) -> Vec<ImportedScene> {
+        self.document()
+            .scenes()
+            .map(|scene| ImportedScene::from(&scene))
+            .collect()
+    }
+
+    /// The scene to show when loading the document
+    ///
+    /// The scene set by `scene` in the document, or the first scene if it is not set. `None` if the document has no
+    /// scenes at all (e.g. a library of meshes).
+    pub fn default_scene(&self) -> Option<ImportedScene> {
+        let document = self.document();
+        document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+            .map(|scene| ImportedScene::from(&scene))
+    }
+
+    /// All mesh instances of the scene with the given index from the `scenes` section of the GLTF document
+    ///
+    /// The node hierarchy is traversed depth-first, in the order of the root nodes and children in the document.
+    /// Meshes referenced by multiple nodes result in multiple instances. `None` if there is no such scene.
+    pub fn scene_instances(&self, scene: usize) -> Option<Vec<MeshInstance>> {
+        let scene = self.document().scenes().nth(scene)?;
+        Some(
+            scene_nodes(&scene)
+                .into_iter()
+                .filter_map(|scene_node| {
+                    Some(MeshInstance {
+                        mesh: scene_node.node.mesh()?.index(),
+                        node: scene_node.node.index(),
+                        node_path: scene_node.path,
+                        transform: scene_node.transform,
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    /// The meshes used by the scene with the given index, in the order of the `meshes` section
+    pub(crate) fn scene_meshes(&self, scene: usize) -> Option<Vec<Mesh<'_>>> {
+        let scene = self.document().scenes().nth(scene)?;
+        let used: HashSet<_> = scene_nodes(&scene)
+            .into_iter()
+            .filter_map(|scene_node| Some(scene_node.node.mesh()?.index()))
+            .collect();
+        Some(
+            self.document()
+                .meshes()
+                .filter(|mesh| used.contains(&mesh.index()))
+                .collect(),
+        )
+    }
+}
+
+/// All nodes of `scene` in depth-first order, with their world transformation
+///
+/// Nodes that are their own ancestor (invalid documents) are skipped instead of being traversed endlessly.
+pub(crate) fn scene_nodes<'a>(scene: &Scene<'a>) -> Vec<SceneNode<'a>> {
+    let mut nodes = Vec::new();
+    for node in scene.nodes() {
+        visit_node(node, Vec::new(), Mat4::identity(), &mut nodes);
+    }
+    nodes
+}
+
+fn visit_node<'a>(
+    node: Node<'a>,
+    mut path: Vec<usize>,
+    parent_transform: Mat4,
+    nodes: &mut Vec<SceneNode<'a>>,
+) {
+    if path.contains(&node.index()) {
+        return;
+    }
+    path.push(node.index());
+    let transform = parent_transform * Mat4::from(node.transform().matrix());
+
+    let children = node.children();
+    nodes.push(SceneNode {
+        node,
+        path: path.clone(),
+        transform,
+    });
+    for child in children {
+        visit_node(child, path.clone(), transform, nodes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::GltfImporter;
+    use three_d::Vec3;
+
+    /// Document with a degenerate triangle as accessor 0, followed by the (JSON) properties `rest`
+    fn triangle_document(rest: &str) -> String {
+        format!(
+            r#"{{
+                "asset": {{ "version": "2.0" }},
+                "buffers": [{{ "byteLength": 36, "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA" }}],
+                "bufferViews": [{{ "buffer": 0, "byteLength": 36 }}],
+                "accessors": [
+                    {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0, 0, 0], "max": [0, 0, 0] }}
+                ],
+                {}
+            }}"#,
+            rest
+        )
+    }
+
+    #[test]
+    fn test_scene_instances() {
+        let document = triangle_document(
+            r#"
+            "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0 } }] }],
+            "nodes": [
+                { "translation": [1, 0, 0], "children": [1, 2] },
+                { "scale": [2, 2, 2], "mesh": 0 },
+                { "matrix": [1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 3, 0, 1], "children": [3] },
+                { "mesh": 0 },
+                { "mesh": 0 }
+            ],
+            "scenes": [{ "nodes": [0] }, { "nodes": [4] }]
+        "#,
+        );
+        GltfImporter::import_from_slice(document.as_bytes(), None, |imported| {
+            let result = imported.unwrap();
+            assert!(result.scene_instances(2).is_none());
+
+            let instances = result.scene_instances(0).unwrap();
+            assert_eq!(instances.len(), 2);
+            assert_eq!(instances[0].node_path, vec![0, 1]);
+            assert_eq!(
+                instances[0].transform,
+                Mat4::from_translation(Vec3::new(1.0, 0.0, 0.0)) * Mat4::from_scale(2.0)
+            );
+            assert_eq!(instances[1].mesh, 0);
+            assert_eq!(instances[1].node, 3);
+            assert_eq!(instances[1].node_path, vec![0, 2, 3]);
+            assert_eq!(
+                instances[1].transform,
+                Mat4::from_translation(Vec3::new(1.0, 3.0, 0.0))
+            );
+
+            let instances = result.scene_instances(1).unwrap();
+            assert_eq!(instances.len(), 1);
+            assert_eq!(instances[0].transform, Mat4::identity());
+        });
+    }
+
+    #[test]
+    fn test_select_scenes() {
+        let document = triangle_document(
+            r#"
+            "materials": [{ "name": "Low" }],
+            "meshes": [
+                { "primitives": [{ "attributes": { "POSITION": 0 } }] },
+                { "primitives": [{ "attributes": { "POSITION": 0 }, "material": 0 }] }
+            ],
+            "nodes": [{ "mesh": 0 }, { "mesh": 1 }, { "children": [1] }],
+            "scenes": [{ "name": "High", "nodes": [0] }, { "name": "Low", "nodes": [2] }],
+            "scene": 1
+        "#,
+        );
+        GltfImporter::import_from_slice(document.as_bytes(), None, |imported| {
+            let result = imported.unwrap();
+            let scenes = result.scenes();
+            assert_eq!(scenes.len(), 2);
+            assert_eq!(scenes[0].name.as_deref(), Some("High"));
+            assert_eq!(scenes[1].nodes, vec![2]);
+            assert_eq!(result.default_scene(), Some(scenes[1].clone()));
+
+            let meshes = result.cpu_meshes_in_scene(1).unwrap();
+            assert_eq!(meshes.keys().collect::<Vec<_>>(), vec![&(1, 0)]);
+            assert_eq!(result.materials_in_scene(1).unwrap().len(), 1);
+            assert!(result.materials_in_scene(0).unwrap().is_empty());
+            assert!(result.cpu_meshes_in_scene(2).is_none());
+        });
+
+        let document = r#"{
+            "asset": { "version": "2.0" },
+            "scenes": [{ "name": "First", "nodes": [] }, { "name": "Second", "nodes": [] }]
+        }"#;
+        GltfImporter::import_from_slice(document.as_bytes(), None, |imported| {
+            let scene = imported.unwrap().default_scene().unwrap();
+            assert_eq!(scene.index, 0);
+            assert!(scene.nodes.is_empty());
+        });
+    }
+}
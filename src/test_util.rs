@@ -0,0 +1,4 @@
+//! Fixtures shared by the tests of several modules
+
+/// A 1x1 PNG image as `data:` URI, for documents with images that are not loaded from files
+pub(crate) const PNG_DATA_URI: &str = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mP8z8BQDwAEhQGAhKmMIQAAAABJRU5ErkJggg==";
@@ -0,0 +1,285 @@
+use crate::import::ImportedGltfModel;
+use crate::ktx2::Ktx2Image;
+use gltf::texture::{MagFilter, MinFilter, Sampler, WrappingMode};
+use gltf::Texture;
+use image::DynamicImage;
+use std::collections::HashMap;
+use three_d::{CPUTexture, Format, Interpolation, Mat3, Wrapping};
+
+/// Imported textures
+///
+/// Keys of the hashmap corresponds to the indexes from the `textures` section of the GLTF document
+pub type ImportedTextures<'a> = HashMap<usize, ImportedTexture<'a>>;
+
+/// A GLTF texture with its decoded image and sampler settings
+#[derive(Clone, Debug)]
+pub struct ImportedTexture<'a> {
+    /// The texture of the GLTF document
+    pub texture: Texture<'a>,
+    /// The decoded image of the texture, see [`ImportedGltfModel::texture_image`](../import/struct.ImportedGltfModel.html#method.texture_image)
+    pub image: &'a DynamicImage,
+    /// The KTX2 image of a texture using the `KHR_texture_basisu` extension, see
+    /// [`ImportedGltfModel::texture_compressed_image`](../import/struct.ImportedGltfModel.html#method.texture_compressed_image)
+    pub compressed_image: Option<&'a Ktx2Image>,
+    /// The sampler of the texture, converted for `three-d`
+    pub sampler: TextureSampler,
+}
+
+impl ImportedTexture<'_> {
+    /// Converts the texture to a RGBA `three-d` texture with the sampler settings applied
+    pub fn to_cpu_texture(&self) -> CPUTexture<u8> {
+        let rgba = self.image.to_rgba8();
+        CPUTexture {
+            width: rgba.width() as usize,
+            height: rgba.height() as usize,
+            data: rgba.into_raw(),
+            format: Format::RGBA8,
+            min_filter: self.sampler.min_filter,
+            mag_filter: self.sampler.mag_filter,
+            mip_map_filter: self.sampler.mip_map_filter,
+            wrap_s: self.sampler.wrap_s,
+            wrap_t: self.sampler.wrap_t,
+            ..Default::default()
+        }
+    }
+}
+
+/// Filtering and wrapping of a texture, as set by its GLTF sampler
+///
+/// Filters not set by the sampler default to linear filtering with linear mip mapping, `three-d`'s defaults.
+#[derive(Clone, Copy, Debug)]
+pub struct TextureSampler {
+    /// Filter used when the texture is minified
+    pub min_filter: Interpolation,
+    /// Filter used when the texture is magnified
+    pub mag_filter: Interpolation,
+    /// Filter used between mip levels, `None` if no mip maps are used
+    pub mip_map_filter: Option<Interpolation>,
+    /// Wrapping of the horizontal texture coordinate
+    pub wrap_s: Wrapping,
+    /// Wrapping of the vertical texture coordinate
+    pub wrap_t: Wrapping,
+}
+
+impl From<&Sampler<'_>> for TextureSampler {
+    fn from(sampler: &Sampler) -> Self {
+        let (min_filter, mip_map_filter) = match sampler.min_filter() {
+            Some(MinFilter::Nearest) => (Interpolation::Nearest, None),
+            Some(MinFilter::Linear) => (Interpolation::Linear, None),
+            Some(MinFilter::NearestMipmapNearest) => {
+                (Interpolation::Nearest, Some(Interpolation::Nearest))
+            }
+            Some(MinFilter::LinearMipmapNearest) => {
+                (Interpolation::Linear, Some(Interpolation::Nearest))
+            }
+            Some(MinFilter::NearestMipmapLinear) => {
+                (Interpolation::Nearest, Some(Interpolation::Linear))
+            }
+            Some(MinFilter::LinearMipmapLinear) | None => {
+                (Interpolation::Linear, Some(Interpolation::Linear))
+            }
+        };
+
+        TextureSampler {
+            min_filter,
+            mag_filter: match sampler.mag_filter() {
+                Some(MagFilter::Nearest) => Interpolation::Nearest,
+                Some(MagFilter::Linear) | None => Interpolation::Linear,
+            },
+            mip_map_filter,
+            wrap_s: wrapping(sampler.wrap_s()),
+            wrap_t: wrapping(sampler.wrap_t()),
+        }
+    }
+}
+
+fn wrapping(mode: WrappingMode) -> Wrapping {
+    match mode {
+        WrappingMode::ClampToEdge => Wrapping::ClampToEdge,
+        WrappingMode::MirroredRepeat => Wrapping::MirroredRepeat,
+        WrappingMode::Repeat => Wrapping::Repeat,
+    }
+}
+
+/// A texture slot of a material
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TextureSlot {
+    /// The base color texture
+    BaseColor,
+    /// The metallic-roughness texture
+    MetallicRoughness,
+    /// The normal texture
+    Normal,
+    /// The occlusion texture
+    Occlusion,
+    /// The emissive texture
+    Emissive,
+}
+
+/// Transformation of the texture coordinates of a texture slot, from the `KHR_texture_transform` extension
+///
+/// Applied as `offset + rotation * scale * uv`, see [`matrix`](#method.matrix).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextureTransform {
+    /// Offset of the texture coordinates
+    pub offset: [f32; 2],
+    /// Counter-clockwise rotation of the texture coordinates in radians
+    pub rotation: f32,
+    /// Scale of the texture coordinates
+    pub scale: [f32; 2],
+    /// Set of texture coordinates to use instead of the one of the texture slot, if any
+    pub tex_coord: Option<u32>,
+}
+
+impl Default for TextureTransform {
+    fn default() -> Self {
+        Self {
+            offset: [0.0, 0.0],
+            rotation: 0.0,
+            scale: [1.0, 1.0],
+            tex_coord: None,
+        }
+    }
+}
+
+impl TextureTransform {
+    /// The 3x3 matrix transforming texture coordinates, given as homogeneous `(u, v, 1)` vectors
+    pub fn matrix(&self) -> Mat3 {
+        let (sin, cos) = self.rotation.sin_cos();
+        let [offset_u, offset_v] = self.offset;
+        let [scale_u, scale_v] = self.scale;
+        Mat3::new(
+            cos * scale_u,
+            -sin * scale_u,
+            0.0,
+            sin * scale_v,
+            cos * scale_v,
+            0.0,
+            offset_u,
+            offset_v,
+            1.0,
+        )
+    }
+
+    /// Transforms the texture coordinates `uv`
+    pub fn apply(&self, [u, v]: [f32; 2]) -> [f32; 2] {
+        let matrix = self.matrix();
+        [
+            matrix.x.x * u + matrix.y.x * v + matrix.z.x,
+            matrix.x.y * u + matrix.y.y * v + matrix.z.y,
+        ]
+    }
+
+    /// Whether the texture coordinates are transformed at all
+    pub fn is_identity(&self) -> bool {
+        self.offset == [0.0, 0.0] && self.rotation == 0.0 && self.scale == [1.0, 1.0]
+    }
+}
+
+impl ImportedGltfModel {
+    /// All textures of the document with a decoded image
+    ///
+    /// Textures whose image is not available (e.g. omitted after failing to load, or a KTX2 image without fallback)
+    /// are left out, use [`texture_compressed_image`](../import/struct.ImportedGltfModel.html#method.texture_compressed_image)
+    /// for the latter.
+    pub fn textures(&self) -> ImportedTextures<'_> {
+        self.document()
+            .textures()
+            .filter_map(|texture| Some((texture.index(), self.import_texture(texture)?)))
+            .collect()
+    }
+
+    /// The texture with the given index from the `textures` section of the GLTF document
+    ///
+    /// `None` if there is no such texture, or its image is not available.
+    pub fn texture(&self, index: usize) -> Option<ImportedTexture<'_>> {
+        self.import_texture(self.document().textures().nth(index)?)
+    }
+
+    fn import_texture<'a>(&'a self, texture: Texture<'a>) -> Option<ImportedTexture<'a>> {
+        Some(ImportedTexture {
+            image: self.texture_image(&texture)?,
+            compressed_image: self.texture_compressed_image(&texture),
+            sampler: TextureSampler::from(&texture.sampler()),
+            texture,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::GltfImporter;
+    use crate::test_util::PNG_DATA_URI;
+
+    #[test]
+    fn test_texture_transform_with_rotation() {
+        // The example of the `KHR_texture_transform` specification
+        let transform = TextureTransform {
+            offset: [0.0, 1.0],
+            rotation: std::f32::consts::FRAC_PI_2,
+            scale: [0.5, 0.5],
+            tex_coord: Some(1),
+        };
+        assert!(!transform.is_identity());
+
+        // `translation * rotation * scale` as given (column-major) by the specification
+        let (sin, cos) = transform.rotation.sin_cos();
+        let translation = Mat3::new(1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0);
+        let rotation = Mat3::new(cos, -sin, 0.0, sin, cos, 0.0, 0.0, 0.0, 1.0);
+        let scale = Mat3::new(0.5, 0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 1.0);
+        let expected: [[f32; 3]; 3] = (translation * rotation * scale).into();
+        let matrix: [[f32; 3]; 3] = transform.matrix().into();
+        for (column, expected) in matrix.iter().zip(expected.iter()) {
+            for (value, expected) in column.iter().zip(expected.iter()) {
+                assert!((value - expected).abs() < 1e-6, "{:?}", matrix);
+            }
+        }
+
+        let cases = [
+            ([0.0, 0.0], [0.0, 1.0]),
+            ([1.0, 0.0], [0.0, 0.5]),
+            ([0.0, 1.0], [0.5, 1.0]),
+            ([1.0, 1.0], [0.5, 0.5]),
+        ];
+        for (uv, [expected_u, expected_v]) in cases.iter() {
+            let [u, v] = transform.apply(*uv);
+            assert!((u - expected_u).abs() < 1e-6 && (v - expected_v).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_import_texture_samplers() {
+        let document = format!(
+            r#"{{
+                "asset": {{ "version": "2.0" }},
+                "samplers": [{{ "magFilter": 9728, "minFilter": 9987, "wrapS": 33071, "wrapT": 33648 }}],
+                "textures": [{{ "source": 0, "sampler": 0 }}, {{ "source": 0 }}],
+                "images": [
+                    {{ "uri": "{png}" }}
+                ]
+            }}"#,
+            png = PNG_DATA_URI
+        );
+        GltfImporter::import_from_slice(document.as_bytes(), None, |imported| {
+            let result = imported.unwrap();
+            assert_eq!(result.textures().len(), 2);
+            assert!(result.texture(2).is_none());
+
+            let sampler = result.texture(0).unwrap().sampler;
+            assert!(matches!(sampler.mag_filter, Interpolation::Nearest));
+            assert!(matches!(sampler.min_filter, Interpolation::Linear));
+            assert!(matches!(
+                sampler.mip_map_filter,
+                Some(Interpolation::Linear)
+            ));
+            assert!(matches!(sampler.wrap_s, Wrapping::ClampToEdge));
+            assert!(matches!(sampler.wrap_t, Wrapping::MirroredRepeat));
+
+            let texture = result.texture(1).unwrap().to_cpu_texture();
+            assert_eq!((texture.width, texture.height), (1, 1));
+            assert!(matches!(texture.mag_filter, Interpolation::Linear));
+            assert!(matches!(texture.wrap_s, Wrapping::Repeat));
+        });
+    }
+}
@@ -3,6 +3,7 @@
 #![cfg(target_arch = "wasm32")]
 
 extern crate wasm_bindgen_test;
+use futures_channel::oneshot;
 use gltf::Gltf;
 use js_sys::{Function, Object};
 use std::path::PathBuf;
@@ -56,7 +57,7 @@ async fn test_import_triangle_model() {
             GltfImporter::import(gltf, Some(base), move |imported| {
                 let result = imported.unwrap();
                 assert_imported_doc(&result, 1, 0, resolve, reject);
-            })
+            });
         })
     });
 
@@ -78,7 +79,7 @@ async fn test_import_triangle_model_with_embedded_data() {
             GltfImporter::import(gltf, Some(base), move |imported| {
                 let result = imported.unwrap();
                 assert_imported_doc(&result, 1, 0, resolve, reject);
-            })
+            });
         })
     });
 
@@ -97,7 +98,7 @@ async fn test_import_cube_model() {
             GltfImporter::import(gltf, Some(base), move |imported| {
                 let result = imported.unwrap();
                 assert_imported_doc(&result, 1, 2, resolve, reject);
-            })
+            });
         })
     });
 
@@ -119,7 +120,7 @@ async fn test_import_simple_meshes_model() {
             GltfImporter::import(gltf, Some(base), move |imported| {
                 let result = imported.unwrap();
                 assert_imported_doc(&result, 1, 0, resolve, reject);
-            })
+            });
         })
     });
 
@@ -141,7 +142,7 @@ async fn test_import_simple_meshes_model_with_embedded_data() {
             GltfImporter::import(gltf, Some(base), move |imported| {
                 let result = imported.unwrap();
                 assert_imported_doc(&result, 1, 0, resolve, reject);
-            })
+            });
         })
     });
 
@@ -160,7 +161,7 @@ async fn test_import_fox_model() {
             GltfImporter::import(gltf, Some(base), move |imported| {
                 let result = imported.unwrap();
                 assert_imported_doc(&result, 1, 1, resolve, reject);
-            })
+            });
         })
     });
 
@@ -182,7 +183,7 @@ async fn test_import_fox_model_with_embedded_data() {
             GltfImporter::import(gltf, Some(base), move |imported| {
                 let result = imported.unwrap();
                 assert_imported_doc(&result, 1, 1, resolve, reject);
-            })
+            });
         })
     });
 
@@ -201,7 +202,7 @@ async fn test_import_fox_model_binary() {
             GltfImporter::import(gltf, Some(base), move |imported| {
                 let result = imported.unwrap();
                 assert_imported_doc(&result, 1, 1, resolve, reject);
-            })
+            });
         })
     });
 
@@ -220,7 +221,7 @@ async fn test_import_toy_car_model() {
             GltfImporter::import(gltf, Some(base), move |imported| {
                 let result = imported.unwrap();
                 assert_imported_doc(&result, 1, 8, resolve, reject);
-            })
+            });
         })
     });
 
@@ -242,9 +243,42 @@ async fn test_import_toy_car_model_binary() {
             GltfImporter::import(gltf, Some(base), move |imported| {
                 let result = imported.unwrap();
                 assert_imported_doc(&result, 1, 8, resolve, reject);
-            })
+            });
         })
     });
 
     JsFuture::from(promise).await.unwrap();
 }
+
+#[wasm_bindgen_test]
+async fn test_import_async_cube_model() {
+    let base = PathBuf::from(format!("{}/{}", "..", "sample_models/2.0/Cube/glTF"));
+    let path = base.join("Cube.gltf");
+
+    let (sender, receiver) = oneshot::channel();
+    Loader::load(&[path.clone()], move |loaded| {
+        let _ = sender.send(loaded.bytes(&path).map(<[u8]>::to_vec));
+    });
+    let gltf = Gltf::from_slice(&receiver.await.unwrap().unwrap()).unwrap();
+
+    let result = GltfImporter::import_async(gltf, Some(base)).await.unwrap();
+    assert_eq!(result.buffers().len(), 1);
+    assert_eq!(result.images().len(), 2);
+}
+
+#[wasm_bindgen_test]
+async fn test_import_from_path_fox_model_binary() {
+    let promise = js_sys::Promise::new(&mut |resolve: Function, reject: Function| {
+        let path = PathBuf::from(format!(
+            "{}/{}",
+            "..", "sample_models/2.0/Fox/glTF-Binary/Fox.glb"
+        ));
+
+        GltfImporter::import_from_path(path, move |imported| {
+            let result = imported.unwrap();
+            assert_imported_doc(&result, 1, 1, resolve, reject);
+        });
+    });
+
+    JsFuture::from(promise).await.unwrap();
+}